@@ -0,0 +1,342 @@
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use rand::Rng;
+use crate::settings::{Difficulty, GameSettings, optimal_attempts};
+use crate::solver::{Feedback, Search, Solver, seeded_rng, solver_by_name};
+use crate::stats::{RoundStats, Statistics};
+
+/// A command typed instead of a guess.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Quit,
+    /// Drop the round and start another one.
+    Restart,
+    /// Show the secret number, the round counts as lost.
+    GiveUp,
+    /// Show the guesses of the round.
+    History,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoundOutcome {
+    Won,
+    Lost,
+    GaveUp,
+    /// Reverse mode: the player's answers contradicted each other.
+    Cheated,
+    Restart,
+    Quit,
+}
+
+/// A finished round: how it ended and the guesses made, with their answers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundResult {
+    pub outcome: RoundOutcome,
+    pub guesses: Vec<(u32, Feedback)>,
+}
+
+/// Everything a game needs besides its input and output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameConfig {
+    /// None to pick them in the menu.
+    pub settings: Option<GameSettings>,
+    /// Seed of the secret numbers.
+    pub seed: u64,
+    /// Reverse mode: name of the solver that guesses the player's number.
+    pub solver: Option<String>,
+    /// Where to save the statistics of every session after each round.
+    pub stats_path: Option<PathBuf>,
+}
+
+impl Command {
+    pub fn parse(input: &str) -> Option<Command> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "quit" | "exit" => Some(Command::Quit),
+            "restart" => Some(Command::Restart),
+            "giveup" | "give up" => Some(Command::GiveUp),
+            "history" => Some(Command::History),
+            _ => None,
+        }
+    }
+}
+
+impl RoundResult {
+    pub fn attempts(&self) -> u32 {
+        self.guesses.len() as u32
+    }
+}
+
+/// Read a guess in the range of `settings`. Negative numbers and numbers
+/// out of the range get their own message, only words are not numbers.
+pub fn parse_guess(input: &str, settings: &GameSettings) -> Result<u32, String> {
+    let input = input.trim();
+    let digits = input.strip_prefix('-').unwrap_or(input);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err("Please type a number!".to_string())
+    }
+    if input.starts_with('-') {
+        return Err(format!("{} is negative, the secret number is from {} to {}.", input, settings.min, settings.max))
+    }
+    match input.parse() {
+        Ok(number) if settings.contains(number) => Ok(number),
+        _ => Err(format!("{} is out of the range {} to {}.", input, settings.min, settings.max)),
+    }
+}
+
+// A line of input, None once the input is closed.
+fn read_line<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None)
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+fn ask<R: BufRead, W: Write>(input: &mut R, output: &mut W, prompt: &str) -> io::Result<Option<String>> {
+    write!(output, "{} ", prompt)?;
+    output.flush()?;
+    read_line(input)
+}
+
+fn ask_number<R: BufRead, W: Write>(input: &mut R, output: &mut W, prompt: &str) -> io::Result<Option<u32>> {
+    loop {
+        let line = match ask(input, output, prompt)? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        match line.parse() {
+            Ok(number) => return Ok(Some(number)),
+            Err(_) => writeln!(output, "Please type a whole number from 0 to {}!", u32::MAX)?,
+        }
+    }
+}
+
+fn write_history<W: Write>(output: &mut W, guesses: &[(u32, Feedback)]) -> io::Result<()> {
+    if guesses.is_empty() {
+        return writeln!(output, "No guesses yet.")
+    }
+    let guesses: Vec<String> = guesses.iter().map(|(guess, feedback)| format!("{} {}", guess, feedback)).collect();
+    writeln!(output, "Guesses: {}", guesses.join(", "))
+}
+
+/// Let the player pick a preset or a range of their own. None once the
+/// input is closed.
+pub fn choose_settings<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> io::Result<Option<GameSettings>> {
+    writeln!(output, "Choose a difficulty:")?;
+    for (index, difficulty) in Difficulty::ALL.iter().enumerate() {
+        writeln!(output, "  {}) {:<7}{}", index + 1, difficulty.name(), difficulty.settings())?;
+    }
+    let custom = (Difficulty::ALL.len() + 1).to_string();
+    writeln!(output, "  {}) Custom", custom)?;
+    loop {
+        let choice = match ask(input, output, ">")? {
+            Some(choice) => choice,
+            None => return Ok(None),
+        };
+        if let Some(difficulty) = Difficulty::from_name(&choice) {
+            return Ok(Some(difficulty.settings()))
+        }
+        if choice.eq_ignore_ascii_case("custom") || choice.eq_ignore_ascii_case("c") || choice == custom {
+            break
+        }
+        writeln!(output, "Please pick one of the numbers above.")?;
+    }
+    loop {
+        let (min, max) = match (ask_number(input, output, "Lowest number:")?, ask_number(input, output, "Highest number:")?) {
+            (Some(min), Some(max)) => (min, max),
+            _ => return Ok(None),
+        };
+        if min >= max {
+            writeln!(output, "The highest number must be above the lowest one.")?;
+            continue
+        }
+        let size = u64::from(max) - u64::from(min) + 1;
+        let prompt = format!("Limit the attempts to {}, what a binary search needs? (y/n)", optimal_attempts(size));
+        let limited = match ask(input, output, &prompt)? {
+            Some(answer) => answer.to_ascii_lowercase().starts_with('y'),
+            None => return Ok(None),
+        };
+        return Ok(Some(GameSettings::new(min, max, limited)))
+    }
+}
+
+/// Play one round against `secret`, reading guesses and commands from
+/// `input` until the number is found, the attempts run out or a command
+/// ends the round. The end of the input quits.
+pub fn play_round<R: BufRead, W: Write>(input: &mut R, output: &mut W, settings: &GameSettings, secret: u32) -> io::Result<RoundResult> {
+    let mut guesses = Vec::new();
+    let outcome = loop {
+        let attempts = guesses.len() as u32;
+        match settings.max_attempts {
+            Some(max_attempts) if attempts >= max_attempts => {
+                writeln!(output, "Out of attempts! The secret number was {}.", secret)?;
+                break RoundOutcome::Lost
+            }
+            Some(max_attempts) => writeln!(output, "Please input your guess between {} and {} ({} attempts left).",
+                settings.min, settings.max, max_attempts - attempts)?,
+            None => writeln!(output, "Please input your guess between {} and {}.", settings.min, settings.max)?,
+        }
+        let line = match read_line(input)? {
+            Some(line) => line,
+            None => break RoundOutcome::Quit,
+        };
+        match Command::parse(&line) {
+            Some(Command::Quit) => break RoundOutcome::Quit,
+            Some(Command::Restart) => {
+                writeln!(output, "Starting over with a new number.")?;
+                break RoundOutcome::Restart
+            }
+            Some(Command::GiveUp) => {
+                writeln!(output, "The secret number was {}.", secret)?;
+                break RoundOutcome::GaveUp
+            }
+            Some(Command::History) => {
+                write_history(output, &guesses)?;
+                continue
+            }
+            None => {}
+        }
+        let guess = match parse_guess(&line, settings) {
+            Ok(guess) => guess,
+            Err(message) => {
+                writeln!(output, "{}", message)?;
+                continue
+            }
+        };
+        writeln!(output, "You guessed: {}", guess)?;
+        let feedback = Feedback::of(guess, secret);
+        guesses.push((guess, feedback));
+        match feedback {
+            Feedback::TooSmall => writeln!(output, "Too small!")?,
+            Feedback::TooBig => writeln!(output, "Too big!")?,
+            Feedback::Correct => {
+                writeln!(output, "You are correct!")?;
+                break RoundOutcome::Won
+            }
+        }
+    };
+    Ok(RoundResult { outcome, guesses })
+}
+
+/// Reverse mode: `solver` guesses the number the player thinks of, and
+/// the player answers small, big or correct.
+pub fn reverse_round<R: BufRead, W: Write>(input: &mut R, output: &mut W, settings: &GameSettings, solver: &mut dyn Solver) -> io::Result<RoundResult> {
+    writeln!(output, "Think of a number from {} to {}. Answer my guesses with small, big or correct.", settings.min, settings.max)?;
+    let mut search = Search::new(settings);
+    let outcome = 'round: loop {
+        if settings.max_attempts.is_some_and(|max_attempts| search.attempts() >= max_attempts) {
+            writeln!(output, "I'm out of attempts, you win!")?;
+            break RoundOutcome::Lost
+        }
+        let guess = solver.guess(search.low, search.high);
+        let feedback = loop {
+            let line = match ask(input, output, &format!("Is it {}?", guess))? {
+                Some(line) => line,
+                None => break 'round RoundOutcome::Quit,
+            };
+            match Command::parse(&line) {
+                Some(Command::Quit) => break 'round RoundOutcome::Quit,
+                Some(Command::Restart) => break 'round RoundOutcome::Restart,
+                Some(Command::History) => write_history(output, &search.answers)?,
+                Some(Command::GiveUp) | None => match Feedback::parse(&line) {
+                    Some(feedback) => break feedback,
+                    None => writeln!(output, "Please answer small, big or correct.")?,
+                },
+            }
+        };
+        if let Err(cheat) = search.answer(guess, feedback) {
+            writeln!(output, "That can't be right: {}!", cheat)?;
+            break RoundOutcome::Cheated
+        }
+        if feedback == Feedback::Correct {
+            let size = u64::from(settings.max) - u64::from(settings.min) + 1;
+            writeln!(output, "Found it in {} attempts with the {} solver, a binary search needs at most {}.",
+                search.attempts(), solver.name(), optimal_attempts(size))?;
+            solver.learn(guess);
+            break RoundOutcome::Won
+        }
+    };
+    Ok(RoundResult { outcome, guesses: search.answers })
+}
+
+fn write_summary<W: Write>(output: &mut W, session: &Statistics, all_time: &Statistics, solver: Option<&str>) -> io::Result<()> {
+    if session.rounds.is_empty() {
+        return Ok(())
+    }
+    writeln!(output)?;
+    match solver {
+        Some(name) => writeln!(output, "This session, guessed by the {} solver:", name)?,
+        None => writeln!(output, "This session:")?,
+    }
+    write!(output, "{}", session)?;
+    if solver.is_none() && all_time.rounds.len() > session.rounds.len() {
+        writeln!(output)?;
+        writeln!(output, "All time:")?;
+        write!(output, "{}", all_time)?;
+    }
+    Ok(())
+}
+
+/// Play rounds until the player quits or the input ends, then show the
+/// statistics. The rounds the player guessed are added to `all_time`,
+/// saved after each one when the config has a path. Returns the rounds of
+/// this session.
+pub fn play_game<R: BufRead, W: Write>(input: &mut R, output: &mut W, config: &GameConfig, all_time: &mut Statistics) -> io::Result<Statistics> {
+    writeln!(output, "GUESS THE NUMBER GAME!")?;
+    writeln!(output, "----------------------")?;
+    let mut rng = seeded_rng(config.seed);
+    let mut solver = config.solver.as_deref().and_then(|name| solver_by_name(name, !config.seed));
+    let mut session = Statistics::default();
+    let mut settings = match config.settings {
+        Some(settings) => settings,
+        None => match choose_settings(input, output)? {
+            Some(settings) => settings,
+            None => return Ok(session),
+        },
+    };
+
+    loop {
+        let result = match solver.as_mut() {
+            Some(solver) => reverse_round(input, output, &settings, solver.as_mut())?,
+            None => {
+                writeln!(output, "Generate secret number from {}", settings)?;
+                let secret = rng.gen_range(u64::from(settings.min), u64::from(settings.max) + 1) as u32;
+                play_round(input, output, &settings, secret)?
+            }
+        };
+        if matches!(result.outcome, RoundOutcome::Won | RoundOutcome::Lost | RoundOutcome::GaveUp) {
+            let round = RoundStats::new(&settings, result.attempts(), result.outcome == RoundOutcome::Won);
+            session.record(round);
+            // The stats file keeps the guesses of the player, not those
+            // of the solvers.
+            if solver.is_none() {
+                all_time.record(round);
+                if let Some(path) = &config.stats_path {
+                    if let Err(error) = all_time.save(path) {
+                        writeln!(output, "Failed to save the statistics to {}: {}", path.display(), error)?;
+                    }
+                }
+                if round.won {
+                    writeln!(output, "Found in {} attempts, a binary search needs at most {}.", round.attempts, round.optimal())?;
+                }
+            }
+        }
+        match result.outcome {
+            RoundOutcome::Quit => break,
+            RoundOutcome::Restart => continue,
+            _ => {}
+        }
+
+        let prompt = "Press Enter to play again, type 'menu' to change the range or 'quit' to stop:";
+        match ask(input, output, prompt)?.map(|choice| choice.to_ascii_lowercase()).as_deref() {
+            None | Some("quit") => break,
+            Some("menu") => match choose_settings(input, output)? {
+                Some(chosen) => settings = chosen,
+                None => break,
+            },
+            _ => {}
+        }
+    }
+    write_summary(output, &session, all_time, solver.as_ref().map(|solver| solver.name()))?;
+    Ok(session)
+}
@@ -0,0 +1,15 @@
+// App: Guess the number game - game logic over any input and output
+#[macro_use]
+extern crate serde_derive;
+
+mod settings;
+pub use settings::*;
+
+mod stats;
+pub use stats::*;
+
+mod solver;
+pub use solver::*;
+
+mod game;
+pub use game::*;
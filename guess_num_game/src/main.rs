@@ -1,51 +1,55 @@
+use std::env;
 use std::io;
-use std::cmp::Ordering;
+use std::process;
 use rand::Rng;
-
-const MIN_NUM: u32 = 1;
-const MAX_NUM: u32 = 101;
+use guess_number_game::{GameConfig, Statistics, USAGE, benchmark, optimal_attempts, parse_args, parse_bench_args, play_game};
+
+fn bench<I: Iterator<Item = String>>(args: I) {
+    let options = parse_bench_args(args).unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", USAGE);
+        return
+    }
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let settings = options.settings;
+    println!("{} rounds from {} to {}, seed {}", options.rounds, settings.min, settings.max, seed);
+    println!("A binary search needs at most {} attempts.", optimal_attempts(settings.size()));
+    for result in benchmark(&settings, options.rounds, seed) {
+        println!("{:<14} average {:>6.2}, worst {}", result.solver, result.average, result.worst);
+    }
+}
 
 fn main() {
-    println!("GUESS THE NUMBER GAME!");
-    println!("----------------------");
-
-    loop {
-        println!("Generate secret number between {} and {}", MIN_NUM, MAX_NUM);
-        let secret_number = rand::thread_rng().gen_range(MIN_NUM, MAX_NUM);
-
-        // println!("The secret number is: {}", secret_number);
-        loop {
-            println!("Please input your guess.");
-
-            let mut guess = String::new();
-
-            match io::stdin().read_line(&mut guess) {
-                Ok(string) => string,
-                Err(_) => {
-                    println!("Failed to read input!");
-                    continue;
-                }
-            };
-
-            
-            let guess: u32 = match guess.trim().parse() {
-                Ok(num) => num,
-                Err(_) => {
-                    println!("Please type a number!");
-                    continue;
-                }
-            };
-
-            println!("You guessed: {}", guess);
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("bench") {
+        return bench(args.skip(1))
+    }
+    let options = parse_args(args).unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", USAGE);
+        return
+    }
 
-            match guess.cmp(&secret_number) {
-                Ordering::Less => println!("Too small!"),
-                Ordering::Greater => println!("Too big!"),
-                Ordering::Equal => {
-                    println!("You are correct!");
-                    break;
-                }
-            }
-        }
+    let mut all_time = Statistics::load(&options.stats_path).unwrap_or_else(|error| {
+        eprintln!("Failed to load the statistics from {}: {}", options.stats_path.display(), error);
+        Statistics::default()
+    });
+    let config = GameConfig {
+        settings: options.settings,
+        seed: options.seed.unwrap_or_else(|| rand::thread_rng().gen()),
+        solver: if options.reverse { Some(options.solver) } else { None },
+        stats_path: Some(options.stats_path),
+    };
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(error) = play_game(&mut stdin.lock(), &mut stdout.lock(), &config, &mut all_time) {
+        eprintln!("The game stopped: {}", error);
+        process::exit(1);
     }
 }
@@ -0,0 +1,303 @@
+use std::fmt;
+use std::path::PathBuf;
+use crate::solver::SOLVER_NAMES;
+use crate::stats::DEFAULT_STATS_PATH;
+
+pub const USAGE: &str = "\
+Usage: guess_number_game [options]
+       guess_number_game bench [--rounds <count>] [--seed <number>] [range options]
+
+Options:
+    --difficulty <level>
+                   Preset range and attempts: easy, normal or hard.
+    --min <number> Lowest secret number (default 1).
+    --max <number> Highest secret number, included (default 100).
+    --attempts <count>
+                   Guesses allowed in a round.
+    --limited      Limit the guesses to what a binary search needs for the
+                   range.
+    --reverse      You pick the number and the computer guesses it. Answer
+                   small, big or correct.
+    --solver <name>
+                   How the computer guesses: binary, random or interpolation
+                   (default binary).
+    --seed <number>
+                   Seed of the secret numbers, to play the same game again.
+    --stats <path> Statistics file (default guess_number_game_stats.json).
+    --help         Show this help.
+
+Without options, the game asks for a difficulty at startup.
+
+While guessing, type 'history' to see your guesses, 'giveup' to see the
+number, 'restart' for a new number or 'quit' to stop.
+
+bench runs every solver over the same seeded secrets and prints their
+average attempts. Default 1000 rounds from 1 to 100.";
+
+pub const DEFAULT_BENCH_ROUNDS: usize = 1000;
+
+/// Preset ranges and attempt limits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+/// Range of the secret number, both ends included, and how many guesses a
+/// round allows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameSettings {
+    pub min: u32,
+    pub max: u32,
+    /// None for unlimited guesses.
+    pub max_attempts: Option<u32>,
+}
+
+/// What the command line asked for. `settings` is None when the player
+/// should pick them in the menu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub settings: Option<GameSettings>,
+    pub stats_path: PathBuf,
+    pub reverse: bool,
+    pub solver: String,
+    pub seed: Option<u64>,
+    pub help: bool,
+}
+
+/// Options of the bench command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchOptions {
+    pub settings: GameSettings,
+    pub rounds: usize,
+    pub seed: Option<u64>,
+    pub help: bool,
+}
+
+/// Guesses a binary search needs at most to find a number in a range of
+/// `size` numbers: floor(log2(size)) + 1.
+pub fn optimal_attempts(size: u64) -> u32 {
+    64 - size.leading_zeros()
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// Parse a difficulty from its name or its menu number.
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "easy" | "e" | "1" => Some(Difficulty::Easy),
+            "normal" | "n" | "2" => Some(Difficulty::Normal),
+            "hard" | "h" | "3" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn settings(self) -> GameSettings {
+        match self {
+            Difficulty::Easy => GameSettings::new(1, 10, false),
+            Difficulty::Normal => GameSettings::new(1, 100, true),
+            Difficulty::Hard => GameSettings::new(1, 1000, true),
+        }
+    }
+}
+
+impl GameSettings {
+    /// Settings for `min..=max`, with as many attempts as a binary search
+    /// needs when `limited`.
+    pub fn new(min: u32, max: u32, limited: bool) -> GameSettings {
+        let mut settings = GameSettings { min, max, max_attempts: None };
+        if limited {
+            settings.max_attempts = Some(optimal_attempts(settings.size()));
+        }
+        settings
+    }
+
+    /// How many numbers the secret may be.
+    pub fn size(&self) -> u64 {
+        u64::from(self.max) - u64::from(self.min) + 1
+    }
+
+    pub fn contains(&self, number: u32) -> bool {
+        (self.min..=self.max).contains(&number)
+    }
+}
+
+impl Default for GameSettings {
+    fn default() -> GameSettings {
+        Difficulty::Normal.settings()
+    }
+}
+
+impl fmt::Display for GameSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} to {}, ", self.min, self.max)?;
+        match self.max_attempts {
+            Some(attempts) => write!(f, "{} attempts", attempts),
+            None => write!(f, "unlimited attempts"),
+        }
+    }
+}
+
+fn parse_number(option: &str, value: Option<String>) -> Result<u32, String> {
+    let value = value.ok_or_else(|| format!("{} expects a number", option))?;
+    value.trim().parse().map_err(|_| format!("{} expects a whole number from 0 to {}, not '{}'", option, u32::MAX, value))
+}
+
+/// Parse the command line arguments, program name excluded.
+pub fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut difficulty = None;
+    let mut min = None;
+    let mut max = None;
+    let mut attempts = None;
+    let mut limited = false;
+    let mut stats_path = PathBuf::from(DEFAULT_STATS_PATH);
+    let mut reverse = false;
+    let mut solver = SOLVER_NAMES[0].to_string();
+    let mut seed = None;
+    let mut help = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--difficulty" => {
+                let name = args.next().ok_or("--difficulty expects easy, normal or hard")?;
+                difficulty = Some(Difficulty::from_name(&name).ok_or_else(|| format!("Unknown difficulty '{}'", name))?);
+            }
+            "--min" => min = Some(parse_number("--min", args.next())?),
+            "--max" => max = Some(parse_number("--max", args.next())?),
+            "--attempts" => attempts = Some(parse_number("--attempts", args.next())?),
+            "--limited" => limited = true,
+            "--reverse" => reverse = true,
+            "--solver" => {
+                solver = args.next().ok_or("--solver expects binary, random or interpolation")?.to_ascii_lowercase();
+                if !SOLVER_NAMES.contains(&solver.as_str()) {
+                    return Err(format!("Unknown solver '{}'", solver))
+                }
+            }
+            "--seed" => {
+                let value = args.next().ok_or("--seed expects a number")?;
+                seed = Some(value.trim().parse().map_err(|_| format!("--seed expects a number, not '{}'", value))?);
+            }
+            "--stats" => stats_path = PathBuf::from(args.next().ok_or("--stats expects a file path")?),
+            "--help" | "-h" => help = true,
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+    if difficulty.is_none() && min.is_none() && max.is_none() && attempts.is_none() && !limited {
+        return Ok(Options { settings: None, stats_path, reverse, solver, seed, help })
+    }
+    // A preset keeps its limit on attempts for a range of your own.
+    let preset = difficulty.map(Difficulty::settings).unwrap_or_default();
+    let limited = limited || (difficulty.is_some() && preset.max_attempts.is_some());
+    let mut settings = GameSettings::new(min.unwrap_or(preset.min), max.unwrap_or(preset.max), limited);
+    if settings.min >= settings.max {
+        return Err(format!("The range {} to {} needs at least two numbers", settings.min, settings.max))
+    }
+    if let Some(attempts) = attempts {
+        if attempts == 0 {
+            return Err("--attempts must be at least 1".to_string())
+        }
+        settings.max_attempts = Some(attempts);
+    }
+    Ok(Options { settings: Some(settings), stats_path, reverse, solver, seed, help })
+}
+
+/// Parse the arguments of the bench command, the range ones as for a game.
+pub fn parse_bench_args<I: Iterator<Item = String>>(mut args: I) -> Result<BenchOptions, String> {
+    let mut rounds = DEFAULT_BENCH_ROUNDS;
+    let mut seed = None;
+    let mut help = false;
+    let mut range_args = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rounds" => {
+                let value = args.next().ok_or("--rounds expects a number")?;
+                rounds = value.trim().parse().map_err(|_| format!("--rounds expects a number, not '{}'", value))?;
+            }
+            "--seed" => {
+                let value = args.next().ok_or("--seed expects a number")?;
+                seed = Some(value.trim().parse().map_err(|_| format!("--seed expects a number, not '{}'", value))?);
+            }
+            "--difficulty" | "--min" | "--max" | "--attempts" => {
+                range_args.push(arg);
+                range_args.extend(args.next());
+            }
+            "--limited" => range_args.push(arg),
+            "--help" | "-h" => help = true,
+            _ => return Err(format!("Unknown bench argument '{}'", arg)),
+        }
+    }
+    let options = parse_args(range_args.into_iter())?;
+    Ok(BenchOptions { settings: options.settings.unwrap_or_default(), rounds, seed, help })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+        line.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn ranges_include_both_ends() {
+        let settings = parse_args(args("--min 1 --max 100")).unwrap().settings.unwrap();
+        assert_eq!((settings.min, settings.max, settings.size()), (1, 100, 100));
+        assert!(settings.contains(1) && settings.contains(100));
+        assert!(!settings.contains(0) && !settings.contains(101));
+        assert_eq!(settings.max_attempts, None);
+        assert_eq!(parse_args(args("")).unwrap().settings, None, "no range options opens the menu");
+    }
+
+    #[test]
+    fn bad_ranges_and_attempts_are_refused() {
+        assert!(parse_args(args("--min 10 --max 10")).is_err());
+        assert!(parse_args(args("--min 50 --max 20")).is_err());
+        assert!(parse_args(args("--min 200")).is_err(), "above the default highest number");
+        assert!(parse_args(args("--attempts 0")).is_err());
+        assert!(parse_args(args("--max ten")).is_err());
+        assert!(parse_args(args("--difficulty extreme")).is_err());
+        let settings = parse_args(args("--attempts 3")).unwrap().settings.unwrap();
+        assert_eq!(settings, GameSettings { max_attempts: Some(3), ..GameSettings::new(1, 100, false) });
+    }
+
+    #[test]
+    fn presets_keep_their_limit_for_a_custom_range() {
+        let settings = parse_args(args("--difficulty hard --min 1 --max 50")).unwrap().settings.unwrap();
+        assert_eq!(settings, GameSettings { min: 1, max: 50, max_attempts: Some(6) });
+        let settings = parse_args(args("--difficulty easy --max 500")).unwrap().settings.unwrap();
+        assert_eq!(settings, GameSettings { min: 1, max: 500, max_attempts: None });
+        let settings = parse_args(args("--difficulty normal --attempts 12")).unwrap().settings.unwrap();
+        assert_eq!(settings.max_attempts, Some(12));
+        assert_eq!(Difficulty::from_name(" 3 "), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::Hard.settings().to_string(), "1 to 1000, 10 attempts");
+    }
+
+    #[test]
+    fn binary_search_bounds() {
+        assert_eq!(optimal_attempts(1), 1);
+        assert_eq!(optimal_attempts(2), 2);
+        assert_eq!(optimal_attempts(100), 7);
+        assert_eq!(optimal_attempts(1 << 32), 33);
+        assert_eq!(GameSettings::new(0, u32::MAX, true).max_attempts, Some(33));
+    }
+
+    #[test]
+    fn bench_takes_range_options_only() {
+        let options = parse_bench_args(args("--rounds 50 --seed 9 --difficulty hard --max 500")).unwrap();
+        assert_eq!(options, BenchOptions { settings: GameSettings::new(1, 500, true), rounds: 50, seed: Some(9), help: false });
+        assert_eq!(parse_bench_args(args("")).unwrap().settings, GameSettings::default());
+        assert!(parse_bench_args(args("--help")).unwrap().help);
+        assert!(parse_bench_args(args("--reverse")).is_err());
+        assert!(parse_bench_args(args("--solver random")).is_err());
+        assert!(parse_bench_args(args("--min")).is_err());
+    }
+}
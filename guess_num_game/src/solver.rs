@@ -0,0 +1,313 @@
+use std::cmp::Ordering;
+use std::fmt;
+use rand::{Rng, SeedableRng, XorShiftRng};
+use crate::settings::GameSettings;
+
+/// How the player answers a guess of the computer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Feedback {
+    TooSmall,
+    TooBig,
+    Correct,
+}
+
+/// Answers that leave no number possible: the player said `small` is too
+/// small and `big` is too big, with nothing between them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cheat {
+    pub small: u32,
+    pub big: u32,
+}
+
+/// A way of picking the next guess among the numbers still possible.
+pub trait Solver {
+    fn name(&self) -> &'static str;
+    /// Next guess, from `low` to `high` included.
+    fn guess(&mut self, low: u32, high: u32) -> u32;
+    /// The secret of a finished round, for strategies that learn.
+    fn learn(&mut self, _secret: u32) {}
+}
+
+/// Always the middle of the range: never more guesses than
+/// `optimal_attempts`.
+pub struct BinarySolver;
+
+/// Any number still possible.
+pub struct RandomSolver {
+    rng: XorShiftRng,
+}
+
+/// Guesses where the secret would be if it were picked like the secrets
+/// of earlier rounds: the median of those still possible, blended halfway
+/// with the middle so that one odd secret can't pull the guesses far off.
+/// Without earlier secrets it is a binary search.
+#[derive(Default)]
+pub struct InterpolationSolver {
+    secrets: Vec<u32>,
+}
+
+/// The numbers still possible after the answers so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Search {
+    pub low: u32,
+    pub high: u32,
+    /// Guesses and their answers, oldest first.
+    pub answers: Vec<(u32, Feedback)>,
+}
+
+/// Attempts of a solver over many secrets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub solver: &'static str,
+    pub rounds: usize,
+    pub average: f64,
+    pub worst: u32,
+}
+
+pub const SOLVER_NAMES: [&str; 3] = ["binary", "random", "interpolation"];
+
+fn middle(low: u32, high: u32) -> u32 {
+    ((u64::from(low) + u64::from(high)) / 2) as u32
+}
+
+pub(crate) fn seeded_rng(seed: u64) -> XorShiftRng {
+    // XorShift must not be seeded with zeros, the constants keep it safe.
+    XorShiftRng::from_seed([seed as u32, (seed >> 32) as u32, 0x9E37_79B9, 0x7F4A_7C15])
+}
+
+/// The solver called `name`, random ones drawing from `seed`.
+pub fn solver_by_name(name: &str, seed: u64) -> Option<Box<dyn Solver>> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "binary" => Some(Box::new(BinarySolver)),
+        "random" => Some(Box::new(RandomSolver::new(seed))),
+        "interpolation" => Some(Box::new(InterpolationSolver::default())),
+        _ => None,
+    }
+}
+
+impl Feedback {
+    /// Read an answer: `small`, `big` or `correct`, or their first letter.
+    pub fn parse(input: &str) -> Option<Feedback> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "s" | "small" | "too small" | "<" => Some(Feedback::TooSmall),
+            "b" | "big" | "too big" | ">" => Some(Feedback::TooBig),
+            "c" | "correct" | "yes" | "=" => Some(Feedback::Correct),
+            _ => None,
+        }
+    }
+
+    /// The honest answer for `guess` when the secret is `secret`.
+    pub fn of(guess: u32, secret: u32) -> Feedback {
+        match guess.cmp(&secret) {
+            Ordering::Less => Feedback::TooSmall,
+            Ordering::Greater => Feedback::TooBig,
+            Ordering::Equal => Feedback::Correct,
+        }
+    }
+}
+
+impl fmt::Display for Feedback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Feedback::TooSmall => write!(f, "too small"),
+            Feedback::TooBig => write!(f, "too big"),
+            Feedback::Correct => write!(f, "correct"),
+        }
+    }
+}
+
+impl fmt::Display for Cheat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "you said {} is too small and {} is too big, no number is left", self.small, self.big)
+    }
+}
+
+impl Solver for BinarySolver {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn guess(&mut self, low: u32, high: u32) -> u32 {
+        middle(low, high)
+    }
+}
+
+impl RandomSolver {
+    pub fn new(seed: u64) -> RandomSolver {
+        RandomSolver { rng: seeded_rng(seed) }
+    }
+}
+
+impl Solver for RandomSolver {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn guess(&mut self, low: u32, high: u32) -> u32 {
+        self.rng.gen_range(u64::from(low), u64::from(high) + 1) as u32
+    }
+}
+
+impl Solver for InterpolationSolver {
+    fn name(&self) -> &'static str {
+        "interpolation"
+    }
+
+    fn guess(&mut self, low: u32, high: u32) -> u32 {
+        let mut possible: Vec<u32> = self.secrets.iter().cloned().filter(|secret| (low..=high).contains(secret)).collect();
+        if possible.is_empty() {
+            return middle(low, high)
+        }
+        possible.sort_unstable();
+        middle(possible[possible.len() / 2], middle(low, high))
+    }
+
+    fn learn(&mut self, secret: u32) {
+        self.secrets.push(secret);
+    }
+}
+
+impl Search {
+    pub fn new(settings: &GameSettings) -> Search {
+        Search { low: settings.min, high: settings.max, answers: Vec::new() }
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.answers.len() as u32
+    }
+
+    /// Narrow the numbers still possible. Fails when the answer contradicts
+    /// an earlier one.
+    pub fn answer(&mut self, guess: u32, feedback: Feedback) -> Result<(), Cheat> {
+        self.answers.push((guess, feedback));
+        match feedback {
+            Feedback::TooSmall if guess >= self.high => Err(self.cheat()),
+            Feedback::TooBig if guess <= self.low => Err(self.cheat()),
+            Feedback::TooSmall => {
+                self.low = guess + 1;
+                Ok(())
+            }
+            Feedback::TooBig => {
+                self.high = guess - 1;
+                Ok(())
+            }
+            Feedback::Correct => Ok(()),
+        }
+    }
+
+    // The closest pair of answers that contradict each other.
+    fn cheat(&self) -> Cheat {
+        let small = self.answers.iter().filter(|(_, feedback)| *feedback == Feedback::TooSmall).map(|(guess, _)| *guess).max();
+        let big = self.answers.iter().filter(|(_, feedback)| *feedback == Feedback::TooBig).map(|(guess, _)| *guess).min();
+        let (guess, _) = self.answers[self.answers.len() - 1];
+        Cheat { small: small.unwrap_or(guess), big: big.unwrap_or(guess) }
+    }
+}
+
+/// Let `solver` find `secret`. Returns the attempts it took.
+pub fn solve(solver: &mut dyn Solver, settings: &GameSettings, secret: u32) -> u32 {
+    let mut search = Search::new(settings);
+    loop {
+        let guess = solver.guess(search.low, search.high);
+        let feedback = Feedback::of(guess, secret);
+        search.answer(guess, feedback).expect("honest answers never contradict");
+        if feedback == Feedback::Correct {
+            solver.learn(secret);
+            return search.attempts()
+        }
+    }
+}
+
+// The secrets of a benchmark.
+fn bench_secrets(settings: &GameSettings, rounds: usize, seed: u64) -> Vec<u32> {
+    let mut rng = seeded_rng(seed);
+    (0..rounds).map(|_| rng.gen_range(u64::from(settings.min), u64::from(settings.max) + 1) as u32).collect()
+}
+
+/// Run every solver over `rounds` secrets drawn from `seed`, the same
+/// secrets for all of them.
+pub fn benchmark(settings: &GameSettings, rounds: usize, seed: u64) -> Vec<BenchResult> {
+    let secrets = bench_secrets(settings, rounds, seed);
+    SOLVER_NAMES.iter()
+        .filter_map(|name| solver_by_name(name, !seed))
+        .map(|mut solver| {
+            let attempts: Vec<u32> = secrets.iter().map(|secret| solve(solver.as_mut(), settings, *secret)).collect();
+            BenchResult {
+                solver: solver.name(),
+                rounds,
+                average: attempts.iter().map(|attempts| f64::from(*attempts)).sum::<f64>() / rounds.max(1) as f64,
+                worst: attempts.iter().cloned().max().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::optimal_attempts;
+
+    fn solvers(seed: u64) -> Vec<Box<dyn Solver>> {
+        SOLVER_NAMES.iter().map(|name| solver_by_name(name, seed).unwrap()).collect()
+    }
+
+    #[test]
+    fn solvers_find_every_secret() {
+        let settings = GameSettings::new(1, 20, false);
+        for mut solver in solvers(7) {
+            let bound = if solver.name() == "binary" { optimal_attempts(settings.size()) } else { settings.size() as u32 };
+            for secret in 1..=20 {
+                let attempts = solve(solver.as_mut(), &settings, secret);
+                assert!(attempts >= 1 && attempts <= bound, "{} took {} attempts for {}", solver.name(), attempts, secret);
+            }
+        }
+        assert_eq!(solve(&mut BinarySolver, &GameSettings::new(0, u32::MAX, false), u32::MAX), 33);
+    }
+
+    #[test]
+    fn interpolation_learns_from_earlier_secrets() {
+        let mut solver = InterpolationSolver::default();
+        assert_eq!(solver.guess(1, 100), 50);
+        solver.learn(90);
+        assert_eq!(solver.guess(1, 100), 70);
+        assert_eq!(solver.guess(1, 80), 40, "secrets out of the range are ignored");
+    }
+
+    #[test]
+    fn contradictions_are_caught_at_the_edges() {
+        let settings = GameSettings::new(0, u32::MAX, false);
+        let mut search = Search::new(&settings);
+        assert_eq!(search.answer(0, Feedback::TooBig), Err(Cheat { small: 0, big: 0 }));
+        let mut search = Search::new(&settings);
+        assert_eq!(search.answer(u32::MAX, Feedback::TooSmall), Err(Cheat { small: u32::MAX, big: u32::MAX }));
+
+        let mut search = Search::new(&GameSettings::new(1, 100, false));
+        assert_eq!(search.answer(50, Feedback::TooSmall), Ok(()));
+        assert_eq!(search.answer(51, Feedback::TooBig), Err(Cheat { small: 50, big: 51 }));
+        assert_eq!(Cheat { small: 50, big: 51 }.to_string(), "you said 50 is too small and 51 is too big, no number is left");
+    }
+
+    #[test]
+    fn benchmarks_are_repeatable() {
+        let settings = GameSettings::new(1, 100, false);
+        let results = benchmark(&settings, 200, 42);
+        assert_eq!(results, benchmark(&settings, 200, 42));
+        assert_eq!(results.iter().map(|result| result.solver).collect::<Vec<_>>(), SOLVER_NAMES);
+
+        // Each solver went through the same secrets.
+        let secrets = bench_secrets(&settings, 200, 42);
+        for (result, mut solver) in results.iter().zip(solvers(!42)) {
+            let attempts: Vec<u32> = secrets.iter().map(|secret| solve(solver.as_mut(), &settings, *secret)).collect();
+            assert_eq!(result.worst, attempts.iter().cloned().max().unwrap());
+            assert_eq!(result.average, f64::from(attempts.iter().sum::<u32>()) / 200.0);
+        }
+        assert!(results[0].worst <= 7);
+    }
+
+    #[test]
+    fn empty_benchmarks_average_zero() {
+        for result in benchmark(&GameSettings::default(), 0, 1) {
+            assert_eq!((result.rounds, result.average, result.worst), (0, 0.0, 0));
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::settings::{GameSettings, optimal_attempts};
+
+pub const DEFAULT_STATS_PATH: &str = "guess_number_game_stats.json";
+
+// Widest bar of the histogram, in characters.
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// One finished round.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RoundStats {
+    pub min: u32,
+    pub max: u32,
+    pub attempts: u32,
+    pub won: bool,
+}
+
+/// Rounds played, this session or since the stats file was started.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Statistics {
+    pub rounds: Vec<RoundStats>,
+}
+
+impl RoundStats {
+    pub fn new(settings: &GameSettings, attempts: u32, won: bool) -> RoundStats {
+        RoundStats { min: settings.min, max: settings.max, attempts, won }
+    }
+
+    /// Guesses a binary search needs at most for the range of the round.
+    pub fn optimal(&self) -> u32 {
+        optimal_attempts(u64::from(self.max) - u64::from(self.min) + 1)
+    }
+
+    /// Attempts over the optimal count, negative for a lucky round.
+    pub fn excess(&self) -> i64 {
+        i64::from(self.attempts) - i64::from(self.optimal())
+    }
+}
+
+impl Statistics {
+    pub fn load(path: &Path) -> io::Result<Statistics> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Statistics::default()),
+            Err(error) => return Err(error),
+        };
+        serde_json::from_str(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, text)
+    }
+
+    pub fn record(&mut self, round: RoundStats) {
+        self.rounds.push(round);
+    }
+
+    pub fn won_rounds(&self) -> impl Iterator<Item = &RoundStats> {
+        self.rounds.iter().filter(|round| round.won)
+    }
+
+    /// Won rounds by their number of attempts.
+    pub fn histogram(&self) -> BTreeMap<u32, usize> {
+        let mut histogram = BTreeMap::new();
+        for round in self.won_rounds() {
+            *histogram.entry(round.attempts).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// The won round closest to the optimal count, the fewest attempts
+    /// first among equals.
+    pub fn best_round(&self) -> Option<&RoundStats> {
+        self.won_rounds().min_by_key(|round| (round.excess(), round.attempts))
+    }
+
+    /// Average attempts of the won rounds.
+    pub fn average_attempts(&self) -> Option<f64> {
+        average(self.won_rounds().map(|round| round.attempts))
+    }
+
+    /// Average optimal count of the won rounds, to compare with
+    /// `average_attempts`.
+    pub fn average_optimal(&self) -> Option<f64> {
+        average(self.won_rounds().map(RoundStats::optimal))
+    }
+}
+
+fn average<I: Iterator<Item = u32>>(values: I) -> Option<f64> {
+    let (count, total) = values.fold((0u32, 0u64), |(count, total), value| (count + 1, total + u64::from(value)));
+    if count == 0 {
+        return None
+    }
+    Some(total as f64 / f64::from(count))
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let won = self.won_rounds().count();
+        writeln!(f, "Rounds: {} played, {} won", self.rounds.len(), won)?;
+        if let (Some(attempts), Some(optimal)) = (self.average_attempts(), self.average_optimal()) {
+            writeln!(f, "Average: {:.2} attempts, a binary search needs at most {:.2} ({:+.2})", attempts, optimal, attempts - optimal)?;
+        }
+        if let Some(best) = self.best_round() {
+            writeln!(f, "Best round: {} attempts for {} to {} (binary search: {})", best.attempts, best.min, best.max, best.optimal())?;
+        }
+        let histogram = self.histogram();
+        let most = histogram.values().cloned().max().unwrap_or(0);
+        if most > 0 {
+            writeln!(f, "Attempts of the won rounds:")?;
+        }
+        for (attempts, count) in &histogram {
+            let bar = (count * HISTOGRAM_WIDTH).div_ceil(most);
+            writeln!(f, "{:>3} | {} {}", attempts, "#".repeat(bar), count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn round(max: u32, attempts: u32, won: bool) -> RoundStats {
+        RoundStats { min: 1, max, attempts, won }
+    }
+
+    fn sample() -> Statistics {
+        Statistics { rounds: vec![round(100, 5, true), round(100, 7, true), round(10, 2, true), round(100, 7, false), round(100, 5, true)] }
+    }
+
+    #[test]
+    fn histogram_counts_won_rounds() {
+        let histogram: Vec<(u32, usize)> = sample().histogram().into_iter().collect();
+        assert_eq!(histogram, vec![(2, 1), (5, 2), (7, 1)]);
+        assert!(Statistics::default().histogram().is_empty());
+    }
+
+    #[test]
+    fn best_round_is_the_closest_to_optimal() {
+        // 2 attempts for 1 to 10 is 2 under the 4 of a binary search.
+        assert_eq!(sample().best_round(), Some(&round(10, 2, true)));
+        let mut stats = Statistics::default();
+        stats.record(round(100, 1, false));
+        assert_eq!(stats.best_round(), None, "lost rounds don't count");
+    }
+
+    #[test]
+    fn averages_compare_with_binary_search() {
+        let stats = sample();
+        assert_eq!(stats.average_attempts(), Some(19.0 / 4.0));
+        assert_eq!(stats.average_optimal(), Some(25.0 / 4.0));
+        assert_eq!(Statistics::default().average_attempts(), None);
+        let text = stats.to_string();
+        assert!(text.starts_with("Rounds: 5 played, 4 won\n"));
+        assert!(text.contains("Average: 4.75 attempts, a binary search needs at most 6.25 (-1.50)"));
+        assert!(text.contains("Best round: 2 attempts for 1 to 10 (binary search: 4)"));
+        assert!(text.contains(&format!("  5 | {} 2", "#".repeat(HISTOGRAM_WIDTH))));
+    }
+
+    #[test]
+    fn statistics_are_saved_and_loaded() {
+        let path = env::temp_dir().join(format!("guess_number_game_stats_{}.json", process::id()));
+        sample().save(&path).unwrap();
+        let loaded = Statistics::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), sample());
+        assert_eq!(Statistics::load(&path).unwrap(), Statistics::default(), "a missing file is no rounds yet");
+    }
+}
@@ -0,0 +1,125 @@
+use std::io::Cursor;
+use guess_number_game::{Difficulty, Feedback, GameConfig, GameSettings, RoundOutcome, RoundStats, Statistics, parse_guess, play_game, play_round};
+
+const SEED: u64 = 2024;
+
+fn config(settings: GameSettings) -> GameConfig {
+    GameConfig { settings: Some(settings), seed: SEED, solver: None, stats_path: None }
+}
+
+// Play a whole game on `input`, returning what was printed and the
+// statistics of the session.
+fn play(config: &GameConfig, input: &str) -> (String, Statistics) {
+    let mut output = Vec::new();
+    let mut all_time = Statistics::default();
+    let session = play_game(&mut Cursor::new(input), &mut output, config, &mut all_time).unwrap();
+    (String::from_utf8(output).unwrap(), session)
+}
+
+// The first secret of the seeded game, found by giving up.
+fn first_secret(config: &GameConfig) -> u32 {
+    let (output, _) = play(config, "giveup\nquit\n");
+    let line = output.lines().find(|line| line.starts_with("The secret number was")).unwrap();
+    line.trim_start_matches("The secret number was ").trim_end_matches('.').parse().unwrap()
+}
+
+#[test]
+fn seeded_games_are_won_with_the_secret() {
+    let config = config(GameSettings::new(1, 100, true));
+    let secret = first_secret(&config);
+    assert_eq!(first_secret(&config), secret, "the same seed gives the same secret");
+
+    let (output, session) = play(&config, &format!("{}\nquit\n", secret));
+    assert!(output.contains("You are correct!"));
+    assert!(output.contains("Found in 1 attempts, a binary search needs at most 7."));
+    assert!(output.contains("Rounds: 1 played, 1 won"));
+    assert_eq!(session.rounds.len(), 1);
+    assert_eq!(session.rounds[0].attempts, 1);
+}
+
+#[test]
+fn limited_rounds_run_out_of_attempts() {
+    let config = config(GameSettings { max_attempts: Some(2), ..GameSettings::new(1, 100, false) });
+    let secret = first_secret(&config);
+    let wrong = if secret == 1 { 2 } else { 1 };
+    let (output, session) = play(&config, &format!("{0}\n{0}\n\nquit\n", wrong));
+    assert!(output.contains("(2 attempts left)"));
+    assert!(output.contains("(1 attempts left)"));
+    assert!(output.contains(&format!("Out of attempts! The secret number was {}.", secret)));
+    assert!(!session.rounds[0].won);
+    assert!(output.contains("Generate secret number from 1 to 100, 2 attempts"), "Enter starts another round");
+}
+
+#[test]
+fn bad_input_never_costs_an_attempt() {
+    let settings = GameSettings::new(1, 100, false);
+    assert_eq!(parse_guess(" 42 ", &settings), Ok(42));
+    assert_eq!(parse_guess("-5", &settings), Err("-5 is negative, the secret number is from 1 to 100.".to_string()));
+    assert_eq!(parse_guess("101", &settings), Err("101 is out of the range 1 to 100.".to_string()));
+    assert_eq!(parse_guess("99999999999", &settings), Err("99999999999 is out of the range 1 to 100.".to_string()));
+    assert_eq!(parse_guess("fifty", &settings), Err("Please type a number!".to_string()));
+    assert_eq!(parse_guess("-", &settings), Err("Please type a number!".to_string()));
+
+    let mut output = Vec::new();
+    let result = play_round(&mut Cursor::new("-5\nabc\n0\n50\n"), &mut output, &settings, 50).unwrap();
+    assert_eq!(result.outcome, RoundOutcome::Won);
+    assert_eq!(result.guesses, vec![(50, Feedback::Correct)]);
+}
+
+#[test]
+fn commands_control_the_round() {
+    let settings = GameSettings::new(1, 100, false);
+    let mut output = Vec::new();
+    let result = play_round(&mut Cursor::new("history\n30\n70\nHISTORY\ngiveup\n"), &mut output, &settings, 50).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("No guesses yet."));
+    assert!(output.contains("Guesses: 30 too small, 70 too big"));
+    assert!(output.contains("The secret number was 50."));
+    assert_eq!(result.outcome, RoundOutcome::GaveUp);
+    assert_eq!(result.attempts(), 2);
+
+    let result = play_round(&mut Cursor::new("restart\n"), &mut Vec::new(), &settings, 50).unwrap();
+    assert_eq!(result.outcome, RoundOutcome::Restart);
+    let result = play_round(&mut Cursor::new("10\nquit\n20\n"), &mut Vec::new(), &settings, 50).unwrap();
+    assert_eq!((result.outcome, result.attempts()), (RoundOutcome::Quit, 1));
+    let result = play_round(&mut Cursor::new("10\n"), &mut Vec::new(), &settings, 50).unwrap();
+    assert_eq!(result.outcome, RoundOutcome::Quit, "the end of the input quits");
+}
+
+#[test]
+fn restarts_and_give_ups_play_on() {
+    let config = config(GameSettings::new(1, 100, false));
+    let (output, session) = play(&config, "restart\ngiveup\n\nquit\n");
+    assert_eq!(output.matches("Generate secret number").count(), 3);
+    assert_eq!(session.rounds.len(), 1, "a restarted round is not counted");
+    assert!(output.contains("Rounds: 1 played, 0 won"));
+}
+
+#[test]
+fn the_menu_picks_the_range() {
+    let config = GameConfig { settings: None, ..config(GameSettings::default()) };
+    let (output, _) = play(&config, "9\n3\nquit\n");
+    assert!(output.contains("Please pick one of the numbers above."));
+    assert!(output.contains(&format!("Generate secret number from {}", Difficulty::Hard.settings())));
+
+    let (output, _) = play(&config, "custom\n-3\n10\n5\n5\n20\ny\nquit\n");
+    assert!(output.contains("The highest number must be above the lowest one."));
+    assert!(output.contains("Generate secret number from 5 to 20, 5 attempts"));
+}
+
+#[test]
+fn reverse_games_catch_inconsistent_answers() {
+    let config = GameConfig { solver: Some("binary".to_string()), ..config(GameSettings::new(1, 100, true)) };
+    let mut all_time = Statistics::default();
+    let mut output = Vec::new();
+    let input = "big\nsmall\nmaybe\nhistory\ncorrect\n\nsmall\nbig\nbig\nsmall\nbig\nsmall\nbig\nquit\n";
+    let session = play_game(&mut Cursor::new(input), &mut output, &config, &mut all_time).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Is it 50? Is it 25? Is it 37? Please answer small, big or correct."));
+    assert!(output.contains("Guesses: 50 too big, 25 too small"));
+    assert!(output.contains("Found it in 3 attempts with the binary solver, a binary search needs at most 7."));
+    assert!(output.contains("That can't be right: you said 57 is too small and 58 is too big, no number is left!"));
+    assert!(output.contains("This session, guessed by the binary solver:\nRounds: 1 played, 1 won"), "the contradiction is not a round");
+    assert_eq!(session.rounds, vec![RoundStats { min: 1, max: 100, attempts: 3, won: true }]);
+    assert!(all_time.rounds.is_empty(), "the stats file is for the player's guesses");
+}
@@ -0,0 +1,145 @@
+use crate::mode::GameMode;
+
+/// Birds found in a row for the Bird Watcher.
+pub const BIRDS_IN_A_ROW: u64 = 5;
+/// Rounds in a row without a hint for Self-reliant.
+pub const ROUNDS_WITHOUT_HINTS: u64 = 10;
+/// Wins in a row for On a Roll.
+pub const WINS_IN_A_ROW: u64 = 10;
+/// Session score for the Centurion.
+pub const CENTURION_SCORE: i64 = 100;
+
+const BIRD_CLASS: &str = "Aves";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstTry,
+    BirdWatcher,
+    NoHints,
+    OnARoll,
+    HardWin,
+    Centurion,
+}
+
+/// A finished round, as far as achievements care.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundRecord<'a> {
+    pub won: bool,
+    /// Wrong guesses of the round.
+    pub guess_count: i64,
+    pub hints: usize,
+    /// Class of the secret animal, like `Aves`.
+    pub class: &'a str,
+    pub mode: GameMode,
+    /// Session score once the round is over.
+    pub score: i64,
+}
+
+/// Achievements of a player and the counters leading to them, kept in
+/// the player profile across sessions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AchievementProgress {
+    pub unlocked: Vec<Achievement>,
+    pub birds_in_a_row: u64,
+    pub rounds_without_hints: u64,
+    pub wins_in_a_row: u64,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 6] = [
+        Achievement::FirstTry,
+        Achievement::BirdWatcher,
+        Achievement::NoHints,
+        Achievement::OnARoll,
+        Achievement::HardWin,
+        Achievement::Centurion,
+    ];
+
+    /// Catalog key of the achievement name.
+    pub fn message_key(self) -> &'static str {
+        match self {
+            Achievement::FirstTry => "achievement-first-try",
+            Achievement::BirdWatcher => "achievement-bird-watcher",
+            Achievement::NoHints => "achievement-no-hints",
+            Achievement::OnARoll => "achievement-on-a-roll",
+            Achievement::HardWin => "achievement-hard-win",
+            Achievement::Centurion => "achievement-centurion",
+        }
+    }
+
+    /// Catalog key of what it takes to unlock the achievement.
+    pub fn description_key(self) -> &'static str {
+        match self {
+            Achievement::FirstTry => "achievement-first-try-description",
+            Achievement::BirdWatcher => "achievement-bird-watcher-description",
+            Achievement::NoHints => "achievement-no-hints-description",
+            Achievement::OnARoll => "achievement-on-a-roll-description",
+            Achievement::HardWin => "achievement-hard-win-description",
+            Achievement::Centurion => "achievement-centurion-description",
+        }
+    }
+}
+
+impl AchievementProgress {
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// Count a finished round. Returns the achievements it unlocked.
+    pub fn record_round(&mut self, round: &RoundRecord) -> Vec<Achievement> {
+        let bird = round.won && round.class.eq_ignore_ascii_case(BIRD_CLASS);
+        self.birds_in_a_row = if bird { self.birds_in_a_row + 1 } else { 0 };
+        self.rounds_without_hints = if round.hints == 0 { self.rounds_without_hints + 1 } else { 0 };
+        self.wins_in_a_row = if round.won { self.wins_in_a_row + 1 } else { 0 };
+
+        let earned = [
+            (Achievement::FirstTry, round.won && round.guess_count == 0 && round.hints == 0),
+            (Achievement::BirdWatcher, self.birds_in_a_row >= BIRDS_IN_A_ROW),
+            (Achievement::NoHints, self.rounds_without_hints >= ROUNDS_WITHOUT_HINTS),
+            (Achievement::OnARoll, self.wins_in_a_row >= WINS_IN_A_ROW),
+            (Achievement::HardWin, round.won && round.mode == GameMode::Hard),
+            (Achievement::Centurion, round.score >= CENTURION_SCORE),
+        ];
+        let mut unlocked = Vec::new();
+        for (achievement, earned) in earned.iter() {
+            if *earned && !self.is_unlocked(*achievement) {
+                self.unlocked.push(*achievement);
+                unlocked.push(*achievement);
+            }
+        }
+        unlocked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(won: bool, class: &str, hints: usize) -> RoundRecord<'_> {
+        RoundRecord { won, guess_count: 2, hints, class, mode: GameMode::Medium, score: 20 }
+    }
+
+    #[test]
+    fn rows_unlock_once_and_reset_on_a_miss() {
+        let mut progress = AchievementProgress::default();
+        for _ in 0..BIRDS_IN_A_ROW - 1 {
+            assert!(progress.record_round(&round(true, "Aves", 0)).is_empty());
+        }
+        progress.record_round(&round(false, "Aves", 0));
+        assert_eq!(progress.birds_in_a_row, 0);
+        for _ in 0..BIRDS_IN_A_ROW - 1 {
+            progress.record_round(&round(true, "Aves", 0));
+        }
+        assert_eq!(progress.record_round(&round(true, "Aves", 1)), vec![Achievement::BirdWatcher]);
+        assert_eq!(progress.rounds_without_hints, 0);
+        assert!(progress.record_round(&round(true, "Aves", 0)).is_empty());
+        assert_eq!(progress.rounds_without_hints, 1);
+
+        for _ in 0..ROUNDS_WITHOUT_HINTS - 2 {
+            progress.record_round(&round(false, "Aves", 0));
+        }
+        assert_eq!(progress.record_round(&round(false, "Aves", 0)), vec![Achievement::NoHints]);
+        let first_try = RoundRecord { guess_count: 0, mode: GameMode::Hard, score: 120, ..round(true, "Reptilia", 0) };
+        assert_eq!(progress.record_round(&first_try), vec![Achievement::FirstTry, Achievement::HardWin, Achievement::Centurion]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+
+/// Latest version of the animal database schema.
+///
+/// 1. A bare array of animals with `name`, `type` and `features`.
+/// 2. An object `{"version": 2, "animals": [...]}`, animals may have the
+///    optional fields below. Version 1 files still load, with the optional
+///    fields missing.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Optional text fields that hints and filters can use.
+pub const FACT_FIELDS: [&str; 4] = ["habitat", "continent", "diet", "conservation_status"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Animal {
+    pub name: String,
+    pub r#type: String, // r# to avoid calling standard method instead of define.
+    pub features: String,
+    // Optional so databases written before the fields existed still load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub habitat: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diet: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conservation_status: Option<String>,
+    /// Other accepted names, e.g. "Orca" for "Killer Whale".
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "deserialize_names")]
+    pub aliases: Vec<String>,
+    /// Free-form labels to build themed sessions, e.g. "pets".
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "deserialize_names")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_path: Option<String>,
+    /// Name in other languages by language code, e.g. `{"fr": "Orque"}`.
+    /// Sessions in that language use it as the secret name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, String>,
+}
+
+/// Database file of schema version 2 and later.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionedAnimals {
+    pub version: u32,
+    pub animals: Vec<Animal>,
+}
+
+impl Animal {
+    pub fn new(name: &str, r#type: &str, features: &str) -> Animal {
+        Animal { name: name.to_string(), r#type: r#type.to_string(), features: features.to_string(), ..Animal::default() }
+    }
+
+    /// The name followed by the aliases and the translated names.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str())
+            .chain(self.aliases.iter().map(String::as_str))
+            .chain(self.translations.values().map(String::as_str))
+    }
+
+    /// Name of the animal in a language, the English name if it has none.
+    pub fn local_name(&self, locale: &str) -> &str {
+        self.translations.get(locale).map_or(&self.name, String::as_str)
+    }
+
+    /// Value of one of the FACT_FIELDS, if the animal has it.
+    pub fn fact(&self, field: &str) -> Option<&str> {
+        match field {
+            "habitat" => self.habitat.as_deref(),
+            "continent" => self.continent.as_deref(),
+            "diet" => self.diet.as_deref(),
+            "conservation_status" => self.conservation_status.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+// Aliases and tags are a list in JSON and TOML, and a string separated by
+// semicolons in a CSV cell.
+fn deserialize_names<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    struct NamesVisitor;
+
+    impl<'de> Visitor<'de> for NamesVisitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a list of names or names separated by ';'")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Vec<String>, E> {
+            Ok(value.split(';').map(str::trim).filter(|name| !name.is_empty()).map(String::from).collect())
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Vec<String>, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<String>, A::Error> {
+            let mut names = Vec::new();
+            while let Some(name) = seq.next_element::<String>()? {
+                names.push(name);
+            }
+            Ok(names)
+        }
+    }
+
+    deserializer.deserialize_any(NamesVisitor)
+}
+
+/// Refuse databases written for a newer version of the game.
+pub fn check_schema_version(version: u32) -> Result<(), String> {
+    if version == 0 || version > SCHEMA_VERSION {
+        return Err(format!("unsupported schema version {}, this game reads versions 1 to {}", version, SCHEMA_VERSION))
+    }
+    Ok(())
+}
+
+pub fn generate_animal_data() -> Vec<Animal> {
+    let animal_data_string = include_str!("animal_datas.json");
+    let animal_data: Vec<Animal> = serde_json::from_str(animal_data_string).unwrap_or_else(|error| {
+        panic!("Something wrong when parsing data {:?}", error)
+    });
+    animal_data
+}
@@ -0,0 +1,302 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::animal::{Animal, SCHEMA_VERSION, VersionedAnimals};
+use crate::filter::class_nickname;
+use crate::matching::normalize_name;
+use crate::pack::{PackError, PackFormat, parse_entries, validate_animal};
+
+pub const DEFAULT_PACK_OUTPUT: &str = "animal_pack.json";
+
+/// Write "aves", "AVES" or "Birds" as "Aves".
+pub fn normalize_class(class: &str) -> String {
+    let class = class.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some(classes) = class_nickname(&class) {
+        return classes[0].to_string()
+    }
+    let mut chars = class.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => class,
+    }
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let code = match code.strip_prefix('x').or_else(|| code.strip_prefix('X')) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            std::char::from_u32(code)
+        }
+    }
+}
+
+/// Text of an HTML fragment: tags dropped, entities decoded and
+/// whitespace collapsed.
+pub fn html_text(fragment: &str) -> String {
+    let mut text = String::new();
+    let mut rest = fragment;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '<' => rest = rest.find('>').map_or("", |end| &rest[end + 1..]),
+            '&' => {
+                let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| Some((decode_entity(&rest[1..end])?, end)));
+                match decoded {
+                    Some((decoded, end)) => {
+                        text.push(decoded);
+                        rest = &rest[end + 1..];
+                    }
+                    None => {
+                        text.push('&');
+                        rest = &rest[1..];
+                    }
+                }
+            }
+            _ => {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Contents of every <tag ...>...</tag> element, in document order.
+fn elements<'a>(html: &'a str, tags: &[&str]) -> Vec<&'a str> {
+    // ASCII lowercasing keeps byte offsets, so positions match `html`.
+    let lower = html.to_ascii_lowercase();
+    let mut found = Vec::new();
+    let mut position = 0;
+    while let Some(start) = lower[position..].find('<').map(|offset| position + offset) {
+        position = start + 1;
+        let tag = tags.iter().find(|tag| {
+            lower[start + 1..].starts_with(*tag)
+                && lower[start + 1 + tag.len()..].starts_with(|c: char| c == '>' || c.is_whitespace())
+        });
+        let tag = match tag {
+            Some(tag) => tag,
+            None => continue,
+        };
+        let content_start = match lower[start..].find('>') {
+            Some(offset) => start + offset + 1,
+            None => break,
+        };
+        let content_end = lower[content_start..].find(&format!("</{}", tag)).map_or(lower.len(), |offset| content_start + offset);
+        found.push(&html[content_start..content_end]);
+        position = content_end;
+    }
+    found
+}
+
+/// Read an animal from a saved animal page: the name is the `<h1>`, other
+/// fields come from table rows labelled "Class", "Features", "Habitat",
+/// "Diet", "Location" and "Conservation Status".
+pub fn parse_html_animal(html: &str) -> Option<Animal> {
+    let name = html_text(elements(html, &["h1"]).first()?);
+    let cells: Vec<String> = elements(html, &["td", "th"]).into_iter().map(html_text).collect();
+    let field = |label: &str| -> Option<String> {
+        // Labels look like "Class:", "Features" or "Distinctive Feature".
+        let index = cells.iter().position(|cell| {
+            let cell = cell.trim_end_matches(':').to_lowercase();
+            cell.starts_with(label) || cell.trim_end_matches('s').ends_with(label)
+        })?;
+        cells.get(index + 1).filter(|value| !value.is_empty()).cloned()
+    };
+    Some(Animal {
+        name,
+        r#type: field("class").unwrap_or_default(),
+        features: field("feature").unwrap_or_default(),
+        habitat: field("habitat"),
+        diet: field("diet"),
+        continent: field("location"),
+        conservation_status: field("conservation status"),
+        ..Animal::default()
+    })
+}
+
+/// Builds a word pack from local source files: saved HTML animal pages and
+/// CSV files with the word pack columns.
+#[derive(Debug, Default)]
+pub struct PackBuilder {
+    animals: Vec<Animal>,
+    /// Entries dropped because another source already had the animal.
+    pub duplicates: usize,
+    /// Entries dropped because they can't be played, with the reason.
+    pub skipped: Vec<String>,
+}
+
+impl PackBuilder {
+    pub fn new() -> PackBuilder {
+        PackBuilder::default()
+    }
+
+    pub fn animals(&self) -> &[Animal] {
+        &self.animals
+    }
+
+    /// Add one animal, merging it into a known one with the same name.
+    pub fn add_animal(&mut self, mut animal: Animal, source: &str) {
+        animal.name = animal.name.split_whitespace().collect::<Vec<_>>().join(" ");
+        animal.r#type = normalize_class(&animal.r#type);
+        if let Err(reason) = validate_animal(&animal) {
+            self.skipped.push(format!("{}: {}", source, reason));
+            return
+        }
+        let key = normalize_name(&animal.name);
+        match self.animals.iter_mut().find(|known| normalize_name(&known.name) == key) {
+            Some(known) => {
+                debug!("{}: {} is already in the pack", source, animal.name);
+                self.duplicates += 1;
+                // Earlier sources win, later ones only fill the gaps.
+                known.habitat = known.habitat.take().or(animal.habitat);
+                known.diet = known.diet.take().or(animal.diet);
+                known.continent = known.continent.take().or(animal.continent);
+                known.conservation_status = known.conservation_status.take().or(animal.conservation_status);
+                known.image_path = known.image_path.take().or(animal.image_path);
+                if known.features.is_empty() {
+                    known.features = animal.features;
+                }
+                merge_names(&mut known.aliases, animal.aliases);
+                merge_names(&mut known.tags, animal.tags);
+                for (locale, name) in animal.translations {
+                    known.translations.entry(locale).or_insert(name);
+                }
+            }
+            None => self.animals.push(animal),
+        }
+    }
+
+    pub fn add_html(&mut self, html: &str, source: &str) {
+        match parse_html_animal(html) {
+            Some(animal) => self.add_animal(animal, source),
+            None => self.skipped.push(format!("{}: no <h1> animal name", source)),
+        }
+    }
+
+    /// Add the entries of a word pack in one of the formats the game loads.
+    pub fn add_pack(&mut self, text: &str, format: PackFormat, source: &str) {
+        match parse_entries(text, format) {
+            Ok(entries) => {
+                for (line, animal) in entries {
+                    self.add_animal(animal, &format!("{}:{}", source, line));
+                }
+            }
+            Err((line, message)) => {
+                let line = line.map(|line| format!(":{}", line)).unwrap_or_default();
+                self.skipped.push(format!("{}{}: {}", source, line, message));
+            }
+        }
+    }
+
+    /// Add a source file, or every source file of a directory: saved HTML
+    /// pages (.html, .htm) and word packs (.json, .csv, .toml).
+    pub fn add_source(&mut self, path: &Path) -> Result<(), PackError> {
+        let io_error = |error| PackError::Io { path: path.to_path_buf(), error };
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = fs::read_dir(path).map_err(io_error)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|entry| source_kind(entry).is_some())
+                .collect();
+            // Directory order is arbitrary, sorting keeps builds repeatable.
+            entries.sort();
+            for entry in entries {
+                self.add_source(&entry)?;
+            }
+            return Ok(())
+        }
+        let kind = source_kind(path).ok_or_else(|| PackError::UnsupportedFormat(path.to_path_buf()))?;
+        let text = fs::read_to_string(path).map_err(io_error)?;
+        let source = path.display().to_string();
+        match kind {
+            SourceKind::Html => self.add_html(&text, &source),
+            SourceKind::Pack(format) => self.add_pack(&text, format, &source),
+        }
+        Ok(())
+    }
+
+    /// The animals of the pack, sorted by name.
+    pub fn finish(mut self) -> Vec<Animal> {
+        self.animals.sort_by_key(|animal| normalize_name(&animal.name));
+        self.animals
+    }
+}
+
+// Add the names that aren't known yet, ignoring case and accents.
+fn merge_names(known: &mut Vec<String>, names: Vec<String>) {
+    for name in names {
+        if !known.iter().any(|known| normalize_name(known) == normalize_name(&name)) {
+            known.push(name);
+        }
+    }
+}
+
+enum SourceKind {
+    Html,
+    Pack(PackFormat),
+}
+
+fn source_kind(path: &Path) -> Option<SourceKind> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "html" | "htm" => Some(SourceKind::Html),
+        _ => PackFormat::from_path(path).map(SourceKind::Pack),
+    }
+}
+
+/// Write animals as a word pack of the current schema version.
+pub fn write_pack(animals: Vec<Animal>, path: &Path) -> Result<(), PackError> {
+    if animals.is_empty() {
+        return Err(PackError::Empty(path.to_path_buf()))
+    }
+    let pack = VersionedAnimals { version: SCHEMA_VERSION, animals };
+    let text = serde_json::to_string_pretty(&pack)
+        .map_err(|error| PackError::Invalid { path: path.to_path_buf(), line: None, message: error.to_string() })?;
+    fs::write(path, text).map_err(|error| PackError::Io { path: path.to_path_buf(), error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "<html><body><h1>Snowy&nbsp;Owl</h1>
+        <table>
+          <tr><td>Kingdom:</td><td>Animalia</td></tr>
+          <tr><td class=\"label\">Class:</td><td><a href=\"/aves\">AVES</a></td></tr>
+          <tr><th>Distinctive Feature</th><td>White feathers &amp; yellow eyes</td></tr>
+          <tr><td>Diet</td><td>Carnivore</td></tr>
+        </table></body></html>";
+
+    #[test]
+    fn html_pages_give_the_animal_fields() {
+        let animal = parse_html_animal(PAGE).unwrap();
+        assert_eq!(animal.name, "Snowy Owl");
+        assert_eq!(animal.r#type, "AVES");
+        assert_eq!(animal.features, "White feathers & yellow eyes");
+        assert_eq!(animal.diet.as_deref(), Some("Carnivore"));
+        assert_eq!(animal.habitat, None);
+    }
+
+    #[test]
+    fn builder_merges_duplicates_and_skips_bad_entries() {
+        let mut builder = PackBuilder::new();
+        builder.add_html(PAGE, "owl.html");
+        builder.add_pack("name,type,features,habitat,aliases\nsnowy owl,bird,,Arctic tundra,Arctic Owl\nKiwi,Birds,Tiny wings,,\nR2D2,Droid,,,\n", PackFormat::Csv, "birds.csv");
+        assert_eq!(builder.duplicates, 1);
+        assert_eq!(builder.skipped.len(), 1);
+        let animals = builder.finish();
+        assert_eq!(animals[0].name, "Kiwi");
+        assert_eq!(animals[0].r#type, "Aves");
+        assert_eq!(animals[1].r#type, "Aves");
+        assert_eq!(animals[1].habitat.as_deref(), Some("Arctic tundra"));
+        assert_eq!(animals[1].features, "White feathers & yellow eyes");
+        assert_eq!(animals[1].aliases, vec!["Arctic Owl".to_string()]);
+    }
+}
@@ -0,0 +1,352 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::builder::DEFAULT_PACK_OUTPUT;
+use crate::filter::AnimalFilter;
+use crate::mode::{GameKind, GameMode};
+use crate::multiplayer::{DEFAULT_ROUNDS, MAX_PLAYERS, MIN_PLAYERS};
+use crate::network::DEFAULT_PORT;
+use crate::profile::DEFAULT_SCORES_PATH;
+use crate::reverse::DEFAULT_LEARNED_PACK;
+
+pub const DEFAULT_THINK_MILLIS: u64 = 500;
+
+pub const USAGE: &str = "Usage: guess_word_game [options]
+       guess_word_game pack [pack options] <source>...
+       guess_word_game replay <log> [--lang <code>]
+
+Options:
+    --game <game>  What to play: classic (you guess the animal), hangman (you
+                   guess it letter by letter), anagram (you unscramble its
+                   name), chain (you name an animal starting with the last
+                   letter of the previous one), quiz (you tell its class) or
+                   reverse (the computer guesses yours). Default classic.
+    --mode <mode>  Difficulty: easy, medium or hard. Asked at startup if missing.
+    --player <name>
+                   Profile to play as. Asked at startup if missing.
+    --scores <path>
+                   High-score and profile file (default guess_word_game_scores.json).
+    --db <path>    Load animals from a .json, .csv or .toml word pack instead
+                   of the built-in database. Repeat to merge several packs.
+    --filter <terms>
+                   Only play with some animals, e.g. \"class=birds length=-10\".
+                   Terms: class, length, tag, habitat, continent, diet,
+                   conservation_status. Asked at startup with the mode.
+    --round-time <seconds>
+                   Time limit of each round. Time left adds to the score.
+    --blitz <seconds>
+                   Guess as many animals as possible before the time runs out.
+    --think-ms <milliseconds>
+                   Length of each step of the thinking animation (default 500).
+    --seed <number>
+                   Pick the animals in a repeatable order.
+    --daily        Daily challenge: the seed comes from today's date, so
+                   everyone gets the same animals today.
+    --hot-seat <names>
+                   Hot-seat game: 2 to 4 players, comma separated, take turns
+                   on the same animal.
+    --host <port>  Host a network race on <port> and play in it. The
+                   other players join with --join.
+    --players <count>
+                   Players the host waits for, you included (default 2).
+    --join <address>
+                   Join the race hosted at <host>[:<port>] (default port 7878).
+    --rounds <count>
+                   Rounds of a multiplayer game (default 5).
+    --lang <code>  Language of the game: en, fr or vi, or the path of a
+                   .toml message catalog. Taken from LANG if missing.
+    --record <path>
+                   Log the seed, animals, input and timings of a solo game
+                   to a JSON file, to play it again with `replay` (with
+                   the --lang catalog file the game was played with, if any).
+    --learned <path>
+                   Pack the reverse game loads and learns new animals into
+                   (default learned_animals.json).";
+
+pub const PACK_USAGE: &str = "Usage: guess_word_game pack [options] <source>...
+
+Build a word pack from local files. Sources are saved animal pages
+(.html, .htm), word packs (.json, .csv, .toml) or directories of them.
+Duplicated animals are merged, classes normalised and invalid entries
+skipped.
+
+Options:
+    -o, --output <path>
+                   Where to write the pack (default animal_pack.json).";
+
+/// Options of the `pack` subcommand.
+#[derive(Debug, PartialEq)]
+pub struct PackOptions {
+    pub sources: Vec<PathBuf>,
+    pub output: PathBuf,
+}
+
+/// Who is playing: one player, several at the same keyboard, or a
+/// network race.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayerSetup {
+    Solo,
+    HotSeat(Vec<String>),
+    Host { port: u16, players: usize },
+    Join(String),
+}
+
+/// Options picked on the command line.
+#[derive(Debug)]
+pub struct GameOptions {
+    pub game: GameKind,
+    pub db_paths: Vec<PathBuf>,
+    pub filter: AnimalFilter,
+    pub mode: Option<GameMode>,
+    pub player: Option<String>,
+    pub scores_path: PathBuf,
+    pub round_time: Option<Duration>,
+    pub blitz_time: Option<Duration>,
+    pub anticipate_time: Duration,
+    pub seed: Option<u64>,
+    pub daily: bool,
+    pub players: PlayerSetup,
+    pub rounds: u32,
+    pub lang: Option<String>,
+    pub record_path: Option<PathBuf>,
+    pub learned_path: PathBuf,
+}
+
+impl Default for GameOptions {
+    fn default() -> GameOptions {
+        GameOptions {
+            game: GameKind::Classic,
+            db_paths: Vec::new(),
+            filter: AnimalFilter::default(),
+            mode: None,
+            player: None,
+            scores_path: PathBuf::from(DEFAULT_SCORES_PATH),
+            round_time: None,
+            blitz_time: None,
+            anticipate_time: Duration::from_millis(DEFAULT_THINK_MILLIS),
+            seed: None,
+            daily: false,
+            players: PlayerSetup::Solo,
+            rounds: DEFAULT_ROUNDS,
+            lang: None,
+            record_path: None,
+            learned_path: PathBuf::from(DEFAULT_LEARNED_PACK),
+        }
+    }
+}
+
+fn parse_number(value: Option<String>, flag: &str) -> Result<u64, String> {
+    let value = value.ok_or_else(|| format!("{} expects a number", flag))?;
+    value.parse().map_err(|_| format!("{} expects a number, got '{}'", flag, value))
+}
+
+/// Parse the command line arguments, without the program name.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<GameOptions, String> {
+    let mut options = GameOptions::default();
+    let mut player_count = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--game" => {
+                let name = args.next().ok_or("--game expects classic, hangman, anagram, chain, quiz or reverse")?;
+                options.game = GameKind::from_name(&name).ok_or_else(|| format!("Unknown game '{}'", name))?;
+            }
+            "--db" => {
+                let path = args.next().ok_or("--db expects a word pack path")?;
+                options.db_paths.push(PathBuf::from(path));
+            }
+            "--filter" => {
+                let spec = args.next().ok_or("--filter expects terms like class=birds")?;
+                for term in spec.split_whitespace() {
+                    options.filter.add_term(term)?;
+                }
+            }
+            "--mode" => {
+                let name = args.next().ok_or("--mode expects easy, medium or hard")?;
+                let mode = GameMode::from_name(&name).ok_or_else(|| format!("Unknown game mode '{}'", name))?;
+                options.mode = Some(mode);
+            }
+            "--player" => {
+                options.player = Some(args.next().ok_or("--player expects a name")?);
+            }
+            "--scores" => {
+                options.scores_path = PathBuf::from(args.next().ok_or("--scores expects a file path")?);
+            }
+            "--round-time" => {
+                options.round_time = Some(Duration::from_secs(parse_number(args.next(), "--round-time")?));
+            }
+            "--blitz" => {
+                options.blitz_time = Some(Duration::from_secs(parse_number(args.next(), "--blitz")?));
+            }
+            "--think-ms" => {
+                options.anticipate_time = Duration::from_millis(parse_number(args.next(), "--think-ms")?);
+            }
+            "--seed" => {
+                options.seed = Some(parse_number(args.next(), "--seed")?);
+            }
+            "--daily" => options.daily = true,
+            "--hot-seat" => {
+                let names = args.next().ok_or("--hot-seat expects player names")?;
+                let names: Vec<String> = names.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect();
+                if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&names.len()) {
+                    return Err(format!("--hot-seat expects {} to {} player names", MIN_PLAYERS, MAX_PLAYERS))
+                }
+                options.players = PlayerSetup::HotSeat(names);
+            }
+            "--host" => {
+                let port = parse_number(args.next(), "--host")?;
+                if port > u64::from(u16::MAX) {
+                    return Err(format!("--host expects a port number, got {}", port))
+                }
+                options.players = PlayerSetup::Host { port: port as u16, players: MIN_PLAYERS };
+            }
+            "--players" => {
+                let count = parse_number(args.next(), "--players")? as usize;
+                if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&count) {
+                    return Err(format!("--players expects {} to {} players", MIN_PLAYERS, MAX_PLAYERS))
+                }
+                player_count = Some(count);
+            }
+            "--join" => {
+                let address = args.next().ok_or("--join expects a host address")?;
+                let address = if address.contains(':') { address } else { format!("{}:{}", address, DEFAULT_PORT) };
+                options.players = PlayerSetup::Join(address);
+            }
+            "--rounds" => {
+                options.rounds = parse_number(args.next(), "--rounds")?.max(1) as u32;
+            }
+            "--lang" => {
+                options.lang = Some(args.next().ok_or("--lang expects a language code")?);
+            }
+            "--learned" => {
+                options.learned_path = PathBuf::from(args.next().ok_or("--learned expects a file path")?);
+            }
+            "--record" => {
+                options.record_path = Some(PathBuf::from(args.next().ok_or("--record expects a file path")?));
+            }
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+    if options.record_path.is_some() && options.players != PlayerSetup::Solo {
+        return Err("--record only applies to solo games".to_string())
+    }
+    if (options.round_time.is_some() || options.blitz_time.is_some()) && options.players != PlayerSetup::Solo {
+        return Err("--round-time and --blitz only apply to solo games".to_string())
+    }
+    if options.game != GameKind::Classic && options.players != PlayerSetup::Solo {
+        return Err(format!("The {} game is played alone", options.game.name()))
+    }
+    if options.game == GameKind::Reverse && options.record_path.is_some() {
+        return Err("The reverse game can't be recorded".to_string())
+    }
+    if options.daily && options.seed.is_some() {
+        return Err("--daily and --seed can't be used together".to_string())
+    }
+    if let Some(count) = player_count {
+        match &mut options.players {
+            PlayerSetup::Host { players, .. } => *players = count,
+            _ => return Err("--players only applies to --host".to_string()),
+        }
+    }
+    Ok(options)
+}
+
+/// Parse the arguments of the `pack` subcommand, after `pack`.
+pub fn parse_pack_args<I: IntoIterator<Item = String>>(args: I) -> Result<PackOptions, String> {
+    let mut options = PackOptions { sources: Vec::new(), output: PathBuf::from(DEFAULT_PACK_OUTPUT) };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                options.output = PathBuf::from(args.next().ok_or("--output expects a file path")?);
+            }
+            _ if arg.starts_with('-') => return Err(format!("Unknown argument '{}'", arg)),
+            _ => options.sources.push(PathBuf::from(arg)),
+        }
+    }
+    if options.sources.is_empty() {
+        return Err("pack expects at least one source file or directory".to_string())
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn db_option_can_be_repeated() {
+        let options = parse_args(args("--db birds.csv --db fish.toml")).unwrap();
+        assert_eq!(options.db_paths, vec![PathBuf::from("birds.csv"), PathBuf::from("fish.toml")]);
+        assert!(parse_args(args("--db")).is_err());
+        assert!(parse_args(args("--dbs birds.csv")).is_err());
+    }
+
+    #[test]
+    fn filter_option_adds_terms() {
+        let options = parse_args(vec!["--filter".to_string(), "class=birds length=-10".to_string(), "--filter".to_string(), "tag=pets".to_string()]).unwrap();
+        assert_eq!(options.filter.to_string(), "class=birds length=-10 tag=pets");
+        assert!(parse_args(args("--filter size=big")).is_err());
+    }
+
+    #[test]
+    fn pack_takes_sources_and_an_output() {
+        let options = parse_pack_args(args("pages -o birds.json extra.csv")).unwrap();
+        assert_eq!(options.sources, vec![PathBuf::from("pages"), PathBuf::from("extra.csv")]);
+        assert_eq!(options.output, PathBuf::from("birds.json"));
+        assert_eq!(parse_pack_args(args("pages")).unwrap().output, PathBuf::from(DEFAULT_PACK_OUTPUT));
+        assert!(parse_pack_args(args("-o birds.json")).is_err());
+    }
+
+    #[test]
+    fn game_option_picks_the_game() {
+        assert_eq!(parse_args(args("")).unwrap().game, GameKind::Classic);
+        let options = parse_args(args("--game Reverse --learned mine.json")).unwrap();
+        assert_eq!(options.game, GameKind::Reverse);
+        assert_eq!(options.learned_path, PathBuf::from("mine.json"));
+        assert!(parse_args(args("--game reverse --hot-seat Ann,Bob")).is_err());
+        assert!(parse_args(args("--game chess")).is_err());
+        assert!(parse_args(args("--game hangman --record game.json")).is_ok());
+        assert!(parse_args(args("--game reverse --record game.json")).is_err());
+    }
+
+    #[test]
+    fn mode_option_is_validated() {
+        assert_eq!(parse_args(args("--mode hard")).unwrap().mode, Some(GameMode::Hard));
+        assert_eq!(parse_args(args("")).unwrap().mode, None);
+        assert!(parse_args(args("--mode insane")).is_err());
+    }
+
+    #[test]
+    fn timer_options_take_seconds() {
+        let options = parse_args(args("--round-time 45 --blitz 120 --think-ms 0")).unwrap();
+        assert_eq!(options.round_time, Some(Duration::from_secs(45)));
+        assert_eq!(options.blitz_time, Some(Duration::from_secs(120)));
+        assert_eq!(options.anticipate_time, Duration::from_millis(0));
+        assert!(parse_args(args("--blitz soon")).is_err());
+    }
+
+    #[test]
+    fn daily_challenge_picks_its_own_seed() {
+        assert_eq!(parse_args(args("--seed 99")).unwrap().seed, Some(99));
+        assert!(parse_args(args("--daily")).unwrap().daily);
+        assert!(parse_args(args("--daily --seed 99")).is_err());
+    }
+
+    #[test]
+    fn multiplayer_options() {
+        assert_eq!(parse_args(args("--hot-seat Ann,Bob,Cat")).unwrap().players, PlayerSetup::HotSeat(vec!["Ann".to_string(), "Bob".to_string(), "Cat".to_string()]));
+        assert!(parse_args(args("--hot-seat Ann")).is_err());
+        assert_eq!(parse_args(args("--host 9000 --players 3")).unwrap().players, PlayerSetup::Host { port: 9000, players: 3 });
+        assert!(parse_args(args("--players 3")).is_err());
+        assert_eq!(parse_args(args("--join localhost")).unwrap().players, PlayerSetup::Join("localhost:7878".to_string()));
+        assert_eq!(parse_args(args("--record game.json")).unwrap().record_path, Some(PathBuf::from("game.json")));
+        assert!(parse_args(args("--record game.json --hot-seat Ann,Bob")).is_err());
+        assert!(parse_args(args("--round-time 30 --hot-seat Ann,Bob")).is_err());
+        assert!(parse_args(args("--host 9000 --blitz 60")).is_err());
+        assert!(parse_args(args("--join localhost --round-time 30")).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// Source of time for timers, so tests can move time forward by hand
+/// instead of sleeping.
+pub trait Clock {
+    /// Time elapsed since the clock was created.
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Clock that only moves when told to.
+#[derive(Default)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// A time limit started at some point of a clock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Countdown {
+    started: Duration,
+    limit: Duration,
+}
+
+impl Countdown {
+    pub fn start(now: Duration, limit: Duration) -> Countdown {
+        Countdown { started: now, limit }
+    }
+
+    pub fn remaining(&self, now: Duration) -> Duration {
+        self.limit.checked_sub(now.saturating_sub(self.started)).unwrap_or_default()
+    }
+
+    pub fn expired(&self, now: Duration) -> bool {
+        self.remaining(now) == Duration::from_secs(0)
+    }
+}
+
+/// The "thinking" dots shown before a guess result, one dot per step.
+/// Kept as state so the front end can keep drawing while it plays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reveal {
+    pub input: String,
+    started: Duration,
+    step: Duration,
+}
+
+impl Reveal {
+    pub const STEPS: u32 = 3;
+
+    pub fn new(input: String, now: Duration, step: Duration) -> Reveal {
+        Reveal { input, started: now, step }
+    }
+
+    /// Dots to show at `now`, from 1 to STEPS.
+    pub fn dots(&self, now: Duration) -> usize {
+        let elapsed = now.saturating_sub(self.started);
+        let step = self.step.as_millis().max(1);
+        (elapsed.as_millis() / step + 1).min(Reveal::STEPS as u128) as usize
+    }
+
+    pub fn is_done(&self, now: Duration) -> bool {
+        now.saturating_sub(self.started) >= self.step * Reveal::STEPS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn countdown_runs_on_the_manual_clock() {
+        let clock = ManualClock::new();
+        let countdown = Countdown::start(clock.now(), Duration::from_secs(30));
+        clock.advance(Duration::from_secs(12));
+        assert_eq!(countdown.remaining(clock.now()), Duration::from_secs(18));
+        clock.advance(Duration::from_secs(20));
+        assert!(countdown.expired(clock.now()));
+    }
+
+    #[test]
+    fn reveal_adds_a_dot_per_step() {
+        let clock = ManualClock::new();
+        let reveal = Reveal::new("emu".to_string(), clock.now(), Duration::from_millis(500));
+        assert_eq!(reveal.dots(clock.now()), 1);
+        clock.advance(Duration::from_millis(1200));
+        assert_eq!(reveal.dots(clock.now()), 3);
+        assert!(!reveal.is_done(clock.now()));
+        clock.advance(Duration::from_millis(300));
+        assert!(reveal.is_done(clock.now()));
+    }
+}
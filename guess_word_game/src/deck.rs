@@ -0,0 +1,94 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Draws animal indices in a shuffled order and goes through the whole
+/// database before any animal comes back. The same seed always gives the
+/// same sequence.
+pub struct AnimalDeck {
+    rng: XorShiftRng,
+    order: Vec<usize>,
+    position: usize,
+}
+
+pub(crate) fn seeded_rng(seed: u64) -> XorShiftRng {
+    // XorShift must not be seeded with zeros, the constants keep it safe.
+    XorShiftRng::from_seed([seed as u32, (seed >> 32) as u32, 0x9E37_79B9, 0x7F4A_7C15])
+}
+
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+/// Days since 1970-01-01 (UTC) at `time`.
+pub fn days_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs() / SECONDS_PER_DAY).unwrap_or(0)
+}
+
+/// Seed of the daily challenge: everyone playing on the same (UTC) day
+/// with the same database gets the same animals.
+pub fn daily_seed(day: u64) -> u64 {
+    day.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ 0xDA11_C4A1
+}
+
+impl AnimalDeck {
+    pub fn new(len: usize, seed: u64) -> AnimalDeck {
+        let mut deck = AnimalDeck { rng: seeded_rng(seed), order: (0..len).collect(), position: 0 };
+        deck.shuffle(None);
+        deck
+    }
+
+    // Never start a new pass with the animal that ended the previous one.
+    fn shuffle(&mut self, last: Option<usize>) {
+        self.rng.shuffle(&mut self.order);
+        if self.order.len() > 1 && self.order.first() == last.as_ref() {
+            let swap_with = self.rng.gen_range(1, self.order.len());
+            self.order.swap(0, swap_with);
+        }
+        self.position = 0;
+    }
+
+    pub fn draw(&mut self) -> usize {
+        if self.position == self.order.len() {
+            let last = self.order.last().cloned();
+            self.shuffle(last);
+        }
+        self.position += 1;
+        self.order[self.position - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deck_cycles_through_every_animal() {
+        let mut deck = AnimalDeck::new(5, 42);
+        let mut first_pass: Vec<usize> = (0..5).map(|_| deck.draw()).collect();
+        let last = first_pass[4];
+        first_pass.sort();
+        assert_eq!(first_pass, vec![0, 1, 2, 3, 4]);
+        assert_ne!(deck.draw(), last);
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut deck1 = AnimalDeck::new(50, 7);
+        let mut deck2 = AnimalDeck::new(50, 7);
+        let mut deck3 = AnimalDeck::new(50, 8);
+        let draws1: Vec<usize> = (0..120).map(|_| deck1.draw()).collect();
+        let draws2: Vec<usize> = (0..120).map(|_| deck2.draw()).collect();
+        let draws3: Vec<usize> = (0..120).map(|_| deck3.draw()).collect();
+        assert_eq!(draws1, draws2);
+        assert_ne!(draws1, draws3);
+    }
+
+    #[test]
+    fn daily_seed_changes_every_day() {
+        let day = days_since_epoch(UNIX_EPOCH + std::time::Duration::from_secs(3 * SECONDS_PER_DAY + 100));
+        assert_eq!(day, 3);
+        assert_ne!(daily_seed(day), daily_seed(day + 1));
+    }
+}
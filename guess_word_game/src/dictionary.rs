@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+use crate::animal::Animal;
+use crate::matching::normalize_name;
+
+/// Regular English endings and what the stem may have ended with before
+/// them, e.g. "babies" from "baby" and "making" from "make".
+const INFLECTIONS: [(&str, &[&str]); 13] = [
+    ("'s", &[""]),
+    ("ies", &["y"]),
+    ("es", &["", "e"]),
+    ("s", &[""]),
+    ("ied", &["y"]),
+    ("ed", &["", "e"]),
+    ("ying", &["ie", "y"]),
+    ("ing", &["", "e"]),
+    ("ier", &["y"]),
+    ("iest", &["y"]),
+    ("er", &["", "e"]),
+    ("est", &["", "e"]),
+    ("ly", &["", "le"]),
+];
+
+/// Words a guess may be made of: the bundled word list of the language
+/// plus every word of the animal names and aliases in the database.
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    /// The language has no word list: every word is accepted.
+    any_word: bool,
+}
+
+impl Dictionary {
+    /// The bundled English word list.
+    pub fn english() -> Dictionary {
+        let words = include_str!("english_words.txt")
+            .lines()
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+            .map(String::from)
+            .collect();
+        Dictionary { words, any_word: false }
+    }
+
+    /// The bundled word list of a language, if it has one.
+    pub fn builtin(locale: &str) -> Option<Dictionary> {
+        match locale {
+            "en" => Some(Dictionary::english()),
+            _ => None,
+        }
+    }
+
+    /// Words of `locale` plus the words of every animal name and alias.
+    /// Languages without a word list accept every word.
+    pub fn for_animals(animals: &[Animal], locale: &str) -> Dictionary {
+        let mut dictionary = Dictionary::builtin(locale).unwrap_or(Dictionary { any_word: true, ..Dictionary::default() });
+        dictionary.add_animals(animals);
+        dictionary
+    }
+
+    pub fn add_animals(&mut self, animals: &[Animal]) {
+        for name in animals.iter().flat_map(Animal::names) {
+            self.words.extend(normalize_name(name).split(' ').map(String::from));
+        }
+    }
+
+    /// Whether the word or a regular inflection of it is known: plurals,
+    /// past tenses, "-ing" forms, comparatives and "-ly" adverbs.
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.any_word || self.words.contains(&word) || stems(&word).any(|stem| self.words.contains(&stem))
+    }
+
+    /// Words of `guess` that are not in the dictionary, in order.
+    pub fn unknown_words(&self, guess: &str) -> Vec<String> {
+        normalize_name(guess)
+            .split(' ')
+            .filter(|word| !word.is_empty() && !self.contains(word))
+            .map(String::from)
+            .collect()
+    }
+}
+
+/// Words `word` may be an inflection of. A doubled last consonant is undone
+/// too, as in "running" and "bigger".
+fn stems(word: &str) -> impl Iterator<Item = String> + '_ {
+    INFLECTIONS.iter()
+        .filter_map(move |(suffix, endings)| Some((word.strip_suffix(suffix)?, *endings)))
+        .filter(|(stem, _)| !stem.is_empty())
+        .flat_map(|(stem, endings)| {
+            let mut letters = stem.chars().rev();
+            let undoubled = match (letters.next(), letters.next()) {
+                (Some(last), Some(before)) if last == before && !"aeiou".contains(last) => Some(&stem[..stem.len() - last.len_utf8()]),
+                _ => None,
+            };
+            endings.iter().map(move |ending| format!("{}{}", stem, ending)).chain(undoubled.map(String::from))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animal_names_and_english_words_are_known() {
+        let animals = vec![Animal::new("Axolotl", "Amphibia", "")];
+        let dictionary = Dictionary::for_animals(&animals, "en");
+        assert!(dictionary.unknown_words("Big Axolotl").is_empty());
+        assert!(dictionary.contains("Lion's"));
+        assert_eq!(dictionary.unknown_words("blue xqzt blorp"), vec!["xqzt", "blorp"]);
+    }
+
+    #[test]
+    fn common_and_inflected_words_are_known() {
+        let dictionary = Dictionary::english();
+        for word in ["house", "houses", "running", "walked", "loved", "carried", "happier", "biggest", "babies", "boxes", "making", "dying", "happily", "went", "children", "mice"] {
+            assert!(dictionary.contains(word), "{}", word);
+        }
+        for word in ["xqzts", "blorping", "housx"] {
+            assert!(!dictionary.contains(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn languages_without_a_word_list_accept_every_word() {
+        let animals = vec![Animal::new("Axolotl", "Amphibia", "")];
+        assert!(Dictionary::for_animals(&animals, "fr").unknown_words("maison chien").is_empty());
+        assert!(Dictionary::for_animals(&animals, "vi").contains("nhà"));
+        assert!(Dictionary::builtin("fr").is_none());
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+use crate::animal::{Animal, FACT_FIELDS};
+use crate::matching::normalize_name;
+
+/// Everyday names accepted for the classes of the `type` field, by the
+/// filters, the class quiz and the pack builder. Packs written with an
+/// everyday name get the first class.
+const CLASS_NICKNAMES: [(&str, &[&str]); 10] = [
+    ("bird", &["Aves"]),
+    ("mammal", &["Mammalia"]),
+    ("reptile", &["Reptilia", "Sauropsida"]),
+    ("amphibian", &["Amphibia", "Lissamphibia"]),
+    ("fish", &["Actinopterygii", "Chondrichthyes", "Osteichthyes"]),
+    ("shark", &["Chondrichthyes"]),
+    ("insect", &["Insecta"]),
+    ("arachnid", &["Arachnida"]),
+    ("crustacean", &["Crustacea"]),
+    ("cephalopod", &["Cephalopoda"]),
+];
+
+/// Which animals a session draws from. An empty filter keeps them all.
+///
+/// Written as space separated `key=value` terms, e.g.
+/// `class=birds length=-10 tag=pets diet=carnivore`:
+/// - `class` keeps animals of any of the given classes (comma separated),
+/// - `length` bounds the letters of the name: `4-10`, `-10` or `4-`,
+/// - `tag` keeps animals with every given tag,
+/// - `habitat`, `continent`, `diet` and `conservation_status` keep animals
+///   whose field contains the value.
+///
+/// Underscores in tags and field values stand for spaces.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnimalFilter {
+    pub classes: Vec<String>,
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+    pub tags: Vec<String>,
+    pub facts: Vec<(String, String)>,
+}
+
+/// The classes an everyday name like "bird", "Birds" or "fishes" stands
+/// for.
+pub fn class_nickname(name: &str) -> Option<&'static [&'static str]> {
+    let name = name.trim().to_lowercase();
+    CLASS_NICKNAMES.iter()
+        .find(|(nickname, _)| name == *nickname || name.strip_suffix('s') == Some(nickname) || name.strip_suffix("es") == Some(nickname))
+        .map(|(_, classes)| *classes)
+}
+
+/// Whether `name` is `class` or an everyday name for it.
+pub fn is_class_name(name: &str, class: &str) -> bool {
+    name.trim().eq_ignore_ascii_case(class)
+        || class_nickname(name).is_some_and(|classes| classes.iter().any(|known| known.eq_ignore_ascii_case(class)))
+}
+
+/// Letters of a name, without spaces, hyphens or apostrophes.
+pub fn letter_count(name: &str) -> usize {
+    name.chars().filter(|c| c.is_alphabetic()).count()
+}
+
+fn parse_length(value: &str) -> Result<(Option<usize>, Option<usize>), String> {
+    let bound = |text: &str| -> Result<Option<usize>, String> {
+        if text.is_empty() {
+            return Ok(None)
+        }
+        text.parse().map(Some).map_err(|_| format!("Bad name length '{}', expected a number", text))
+    };
+    match value.find('-') {
+        Some(dash) => Ok((bound(&value[..dash])?, bound(&value[dash + 1..])?)),
+        None => {
+            let exact = bound(value)?;
+            Ok((exact, exact))
+        }
+    }
+}
+
+impl AnimalFilter {
+    pub fn parse(spec: &str) -> Result<AnimalFilter, String> {
+        let mut filter = AnimalFilter::default();
+        for term in spec.split_whitespace() {
+            filter.add_term(term)?;
+        }
+        Ok(filter)
+    }
+
+    /// Add one `key=value` term.
+    pub fn add_term(&mut self, term: &str) -> Result<(), String> {
+        let mut parts = term.splitn(2, '=');
+        let key = parts.next().unwrap_or("").to_ascii_lowercase();
+        let value = parts.next().filter(|value| !value.is_empty())
+            .ok_or_else(|| format!("Filter '{}' should look like key=value", term))?;
+        match key.as_str() {
+            "class" | "type" => self.classes.extend(value.split(',').filter(|class| !class.is_empty()).map(String::from)),
+            "length" | "len" => {
+                let (min_len, max_len) = parse_length(value)?;
+                self.min_len = min_len;
+                self.max_len = max_len;
+            }
+            "tag" => self.tags.push(value.replace('_', " ")),
+            field if FACT_FIELDS.contains(&field) => self.facts.push((field.to_string(), value.replace('_', " "))),
+            _ => return Err(format!("Unknown filter '{}', expected class, length, tag, {}", key, FACT_FIELDS.join(", "))),
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == AnimalFilter::default()
+    }
+
+    fn matches_class(&self, animal: &Animal) -> bool {
+        self.classes.is_empty() || self.classes.iter().any(|class| is_class_name(class, &animal.r#type))
+    }
+
+    pub fn matches(&self, animal: &Animal) -> bool {
+        let len = letter_count(&animal.name);
+        self.matches_class(animal)
+            && self.min_len.is_none_or(|min_len| len >= min_len)
+            && self.max_len.is_none_or(|max_len| len <= max_len)
+            && self.tags.iter().all(|tag| animal.tags.iter().any(|known| normalize_name(known) == normalize_name(tag)))
+            && self.facts.iter().all(|(field, value)| {
+                animal.fact(field).is_some_and(|fact| normalize_name(fact).contains(&normalize_name(value)))
+            })
+    }
+
+    /// The animals the filter keeps, refusing to leave none.
+    pub fn apply(&self, animals: Vec<Animal>) -> Result<Vec<Animal>, String> {
+        let matching: Vec<Animal> = animals.into_iter().filter(|animal| self.matches(animal)).collect();
+        if matching.is_empty() {
+            return Err(format!("No animal matches the filter '{}'", self))
+        }
+        Ok(matching)
+    }
+}
+
+impl fmt::Display for AnimalFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut terms = Vec::new();
+        if !self.classes.is_empty() {
+            terms.push(format!("class={}", self.classes.join(",")));
+        }
+        match (self.min_len, self.max_len) {
+            (None, None) => {}
+            (Some(min_len), Some(max_len)) if min_len == max_len => terms.push(format!("length={}", min_len)),
+            (min_len, max_len) => terms.push(format!("length={}-{}",
+                min_len.map(|len| len.to_string()).unwrap_or_default(),
+                max_len.map(|len| len.to_string()).unwrap_or_default())),
+        }
+        terms.extend(self.tags.iter().map(|tag| format!("tag={}", tag.replace(' ', "_"))));
+        terms.extend(self.facts.iter().map(|(field, value)| format!("{}={}", field, value.replace(' ', "_"))));
+        write!(f, "{}", terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animals() -> Vec<Animal> {
+        let mut kiwi = Animal::new("Kiwi", "Aves", "");
+        kiwi.tags.push("New Zealand".to_string());
+        let mut killer_whale = Animal::new("Killer Whale", "Mammalia", "");
+        killer_whale.diet = Some("Carnivore".to_string());
+        vec![kiwi, killer_whale, Animal::new("Adelie Penguin", "Aves", "")]
+    }
+
+    fn names(animals: &[Animal]) -> Vec<&str> {
+        animals.iter().map(|animal| animal.name.as_str()).collect()
+    }
+
+    #[test]
+    fn filters_combine_every_term() {
+        let birds = AnimalFilter::parse("class=bird length=-10").unwrap().apply(animals()).unwrap();
+        assert_eq!(names(&birds), vec!["Kiwi"]);
+        let carnivores = AnimalFilter::parse("diet=carni").unwrap().apply(animals()).unwrap();
+        assert_eq!(names(&carnivores), vec!["Killer Whale"]);
+        let tagged = AnimalFilter::parse("class=Aves,Mammalia tag=new_zealand").unwrap();
+        assert_eq!(names(&tagged.apply(animals()).unwrap()), vec!["Kiwi"]);
+        let tagged = AnimalFilter::parse("class=Aves,Mammalia tag=New").unwrap();
+        assert!(tagged.apply(animals()).is_err());
+        assert!(AnimalFilter::parse("colour=red").is_err());
+        assert!(AnimalFilter::parse("length=ten").is_err());
+    }
+
+    #[test]
+    fn filter_prints_back_as_terms() {
+        let spec = "class=birds length=4- tag=pets diet=fish_eater";
+        assert_eq!(AnimalFilter::parse(spec).unwrap().to_string(), spec);
+        assert!(AnimalFilter::parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,966 @@
+use std::rc::Rc;
+use std::time::Duration;
+use rand::{Rng, XorShiftRng};
+use crate::animal::Animal;
+use crate::deck::{AnimalDeck, random_seed, seeded_rng};
+use crate::clock::{Clock, Countdown, SystemClock};
+use crate::dictionary::Dictionary;
+use crate::hint::HintTier;
+use crate::locale::Catalog;
+use crate::preview::find_image;
+use crate::puzzle::{animal_classes, chain_answers, chain_letter, check_class_guess, find_animal, scramble};
+use crate::matching::{LetterFeedback, close_distance, is_valid_name_input, letter_feedback, levenshtein, normalize_name};
+use crate::mode::{GameConfig, GameKind};
+use crate::round::Round;
+use crate::scoring::{LOST_ROUND_PENALTY, RoundScore, WORD_BONUS_PER_LETTER};
+use unicode_normalization::UnicodeNormalization;
+
+pub const START_SCORE: i64 = 10;
+pub const MAX_GUESS_COUNT: i64 = 12;
+
+// Define Enum
+#[derive(Debug, PartialEq)]
+pub enum Guess {
+    Right(String),
+    Wrong { message: String, feedback: Vec<LetterFeedback>, close: bool },
+    Invalid(String)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GameCommand {
+    PlayerInput(String),
+    Next,
+    Quit,
+    Scores,
+    Hint,
+}
+
+/// Everything a front end needs to know about what just happened in a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    RoundStarted { word_len: usize },
+    GuessSubmitted(String),
+    GuessRejected(String),
+    GuessWrong { message: String, matched: usize, feedback: Vec<LetterFeedback> },
+    HintUpdated(String),
+    /// A hint bought with the `hint` command.
+    HintGiven { hint: String, cost: i64 },
+    ScoreChanged(i64),
+    /// How the points of a won round add up.
+    RoundScored(RoundScore),
+    /// Hangman: a letter was tried, `found` times in the name.
+    LetterGuessed { message: String, found: usize },
+    /// Multiplayer: it is now this player's turn.
+    TurnChanged(String),
+    /// Multiplayer: this player gave up the round.
+    PlayerPassed(String),
+    /// Multiplayer: this player left, the others play on.
+    PlayerLeft(String),
+    /// Multiplayer: new score of one player.
+    PlayerScored { player: String, score: i64 },
+    RoundWon(String),
+    RoundLost(String),
+    /// Picture of the secret animal to show, by path.
+    ShowPicture(String),
+    GameOver,
+    Quit(String),
+    ShowScores,
+}
+
+/// A wrong guess of the current round, kept for the history pane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuessRecord {
+    pub guess: String,
+    pub feedback: Vec<LetterFeedback>,
+    pub message: String,
+}
+
+/// Game state for a whole play session, free of any terminal I/O.
+/// Feed it player input and render the events it returns.
+pub struct GameSession {
+    animals: Vec<Animal>,
+    dictionary: Dictionary,
+    config: GameConfig,
+    score: i64,
+    guess_count: i64,
+    win_streak: u64,
+    round: Round,
+    /// Anagram: the shuffled name of the round.
+    anagram: String,
+    /// Word chain: animals of the current chain, the one to follow last.
+    chain: Vec<usize>,
+    hints: Vec<HintTier>,
+    hint_rng: XorShiftRng,
+    clock: Rc<dyn Clock>,
+    seed: u64,
+    deck: AnimalDeck,
+    round_timer: Option<Countdown>,
+    blitz_timer: Option<Countdown>,
+    in_round: bool,
+    over: bool,
+    /// Animals with a picture, by name, when they are not looked up on disk.
+    pictures: Option<Vec<String>>,
+}
+
+// Define Functions
+
+pub fn check_game_command(gameinput: &str) -> GameCommand {
+    if gameinput.eq_ignore_ascii_case("next") {
+        return GameCommand::Next
+    }
+    if gameinput.eq_ignore_ascii_case("quit") {
+        return GameCommand::Quit
+    }
+    if gameinput.eq_ignore_ascii_case("scores") {
+        return GameCommand::Scores
+    }
+    if gameinput.eq_ignore_ascii_case("hint") {
+        return GameCommand::Hint
+    }
+    GameCommand::PlayerInput(gameinput.to_string())
+}
+
+/// Compare a guess with the secret name, ignoring case, accents and
+/// hyphens. Guesses made of unknown words are rejected unless they are a
+/// near miss of the answer, and a guess of the wrong length is only
+/// accepted when it is very close. Messages come from `catalog`.
+pub fn check_guess(guess: &str, answer: &str, dictionary: &Dictionary, catalog: &Catalog) -> Guess {
+    if !is_valid_name_input(guess) {
+        return Guess::Invalid(catalog.text("guess-invalid").to_string())
+    }
+    let guess = normalize_name(guess);
+    let normalized_answer = normalize_name(answer);
+    if guess == normalized_answer {
+        return Guess::Right(catalog.format("guess-right", &[("animal", &answer)]))
+    }
+    let answer_len = normalized_answer.chars().count();
+    let close = levenshtein(&guess, &normalized_answer) <= close_distance(answer_len);
+    let unknown_words = dictionary.unknown_words(&guess);
+    if !close && !unknown_words.is_empty() {
+        return Guess::Invalid(catalog.format("guess-unknown-words", &[("words", &unknown_words.join(", "))]))
+    }
+    if guess.chars().count() != answer_len && !close {
+        return Guess::Invalid(catalog.format("guess-wrong-length", &[("length", &answer_len)]))
+    }
+    let message = if close { "guess-close" } else { "guess-wrong" };
+    Guess::Wrong {
+        message: catalog.text(message).to_string(),
+        feedback: letter_feedback(&guess, &normalized_answer),
+        close,
+    }
+}
+
+/// Check a guess against an animal: its name in the language of `catalog`
+/// is the answer, its other names and aliases are accepted as well.
+pub fn check_animal_guess(guess: &str, animal: &Animal, dictionary: &Dictionary, catalog: &Catalog) -> Guess {
+    let answer = animal.local_name(catalog.locale());
+    let normalized_guess = normalize_name(guess);
+    if normalized_guess != normalize_name(answer) {
+        if let Some(alias) = animal.names().find(|name| normalize_name(name) == normalized_guess) {
+            return Guess::Right(catalog.format("guess-right-alias", &[("animal", &answer), ("alias", &alias)]))
+        }
+    }
+    check_guess(guess, answer, dictionary, catalog)
+}
+
+impl GameSession {
+    pub fn new(animals: Vec<Animal>, config: GameConfig) -> GameSession {
+        GameSession::with_clock(animals, config, Rc::new(SystemClock::new()))
+    }
+
+    /// Session whose timers run on `clock`.
+    pub fn with_clock(animals: Vec<Animal>, config: GameConfig, clock: Rc<dyn Clock>) -> GameSession {
+        let seed = config.seed.unwrap_or_else(random_seed);
+        GameSession {
+            seed,
+            deck: AnimalDeck::new(animals.len(), seed),
+            dictionary: Dictionary::for_animals(&animals, config.catalog.locale()),
+            animals,
+            config,
+            score: START_SCORE,
+            guess_count: 0,
+            win_streak: 0,
+            round: Round::default(),
+            anagram: String::new(),
+            chain: Vec::new(),
+            hints: Vec::new(),
+            // Its own stream, so taking hints doesn't change the animals drawn.
+            hint_rng: seeded_rng(!seed),
+            clock,
+            round_timer: None,
+            blitz_timer: None,
+            in_round: false,
+            over: false,
+            pictures: None,
+        }
+    }
+
+    /// Names of the animals that have a picture.
+    pub fn animals_with_pictures(&self) -> Vec<String> {
+        self.animals.iter().filter(|animal| self.has_picture(animal)).map(|animal| animal.name.clone()).collect()
+    }
+
+    /// Offer the picture hint for these animals only, as a recorded session
+    /// had them, instead of looking for pictures on disk.
+    pub fn set_pictures(&mut self, names: Vec<String>) {
+        self.pictures = Some(names);
+    }
+
+    fn has_picture(&self, animal: &Animal) -> bool {
+        match &self.pictures {
+            Some(names) => names.contains(&animal.name),
+            None => find_image(animal).is_some(),
+        }
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+
+    pub fn guess_count(&self) -> i64 {
+        self.guess_count
+    }
+
+    /// Rounds won in a row this session.
+    pub fn win_streak(&self) -> u64 {
+        self.win_streak
+    }
+
+    /// Seed of the animal sequence, to replay the same session.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Animals the session draws from.
+    pub fn animals(&self) -> &[Animal] {
+        &self.animals
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// Wrong guesses allowed in a round: misses in hangman, wrong classes
+    /// in the quiz.
+    pub fn max_guesses(&self) -> i64 {
+        match self.config.kind {
+            GameKind::Hangman => self.config.mode.max_misses(),
+            GameKind::Quiz => self.config.mode.max_quiz_answers(),
+            _ => self.config.mode.max_guess_count(),
+        }
+    }
+
+    pub fn guesses_left(&self) -> i64 {
+        self.max_guesses() - self.guess_count
+    }
+
+    /// Hangman: letters tried this round.
+    pub fn letters(&self) -> &[char] {
+        self.round.letters()
+    }
+
+    /// What the puzzle games ask this round: the shuffled name, the animal
+    /// to follow or the animal to classify.
+    pub fn puzzle(&self) -> Option<String> {
+        let catalog = &self.config.catalog;
+        match self.config.kind {
+            GameKind::Anagram => Some(self.anagram.clone()),
+            GameKind::Chain => self.chain.last().map(|&index| {
+                let name = self.animals[index].local_name(catalog.locale());
+                let letter = chain_letter(name).unwrap_or(' ').to_uppercase().to_string();
+                catalog.format("chain-puzzle", &[("animal", &name), ("letter", &letter), ("length", &self.chain.len())])
+            }),
+            GameKind::Quiz => Some(catalog.format("quiz-puzzle", &[("animal", &self.secret_name())])),
+            _ => None,
+        }
+    }
+
+    /// Word chain: animals of the current chain, oldest first.
+    pub fn chain(&self) -> &[usize] {
+        &self.chain
+    }
+
+    /// The secret animal of the round. In a word chain, the animal the
+    /// hints are about: one of those that may follow.
+    pub fn secret_animal(&self) -> &Animal {
+        &self.animals[self.round.secret_index()]
+    }
+
+    /// Name of the secret animal in the language of the session.
+    pub fn secret_name(&self) -> &str {
+        self.secret_animal().local_name(self.config.catalog.locale())
+    }
+
+    /// Animal family, if the difficulty shows it or it was given as a hint.
+    /// Never in the class quiz, where it is the answer.
+    pub fn visible_type(&self) -> Option<&str> {
+        if self.config.kind == GameKind::Quiz {
+            return None
+        }
+        if self.config.mode.shows_type() || self.hints.contains(&HintTier::Class) {
+            return Some(&self.secret_animal().r#type)
+        }
+        None
+    }
+
+    /// Animal features, if the difficulty shows them or they were given as a hint.
+    pub fn visible_features(&self) -> Option<&str> {
+        if self.config.mode.shows_features() || self.hints.contains(&HintTier::Features) {
+            return Some(&self.secret_animal().features)
+        }
+        None
+    }
+
+    /// Facts from the optional database fields given as hints this round,
+    /// as (tier, value) pairs.
+    pub fn visible_facts(&self) -> Vec<(HintTier, &str)> {
+        let animal = self.secret_animal();
+        self.hints.iter()
+            .filter_map(|tier| tier.fact(animal).map(|fact| (*tier, fact)))
+            .collect()
+    }
+
+    /// Hints given this round, in order.
+    pub fn hints_taken(&self) -> &[HintTier] {
+        &self.hints
+    }
+
+    /// The hint the `hint` command gives next, if any is left.
+    pub fn next_hint(&self) -> Option<HintTier> {
+        HintTier::ALL.iter().cloned().find(|tier| !self.hints.contains(tier) && self.hint_available(*tier))
+    }
+
+    fn hint_available(&self, tier: HintTier) -> bool {
+        match tier {
+            HintTier::Class => self.visible_type().is_none() && self.config.kind != GameKind::Quiz,
+            tier if tier.is_fact() => tier.fact(self.secret_animal()).is_some(),
+            HintTier::FirstLetter => !self.round.hidden_first_letters().is_empty(),
+            HintTier::RandomLetter => !self.round.hidden_letters().is_empty(),
+            HintTier::Features => self.visible_features().is_none(),
+            HintTier::Picture => self.has_picture(self.secret_animal()),
+            _ => false,
+        }
+    }
+
+    pub fn hint(&self) -> String {
+        self.round.hint()
+    }
+
+    /// Wrong guesses of the current round, oldest first.
+    pub fn history(&self) -> &[GuessRecord] {
+        self.round.history()
+    }
+
+    /// Number of letters in the secret name, spaces excluded.
+    pub fn word_len(&self) -> usize {
+        self.round.word_len()
+    }
+
+    /// Time left in the current round, if rounds are timed.
+    pub fn round_time_left(&self) -> Option<Duration> {
+        self.round_timer.map(|timer| timer.remaining(self.clock.now()))
+    }
+
+    /// Time left in a blitz session.
+    pub fn blitz_time_left(&self) -> Option<Duration> {
+        self.blitz_timer.map(|timer| timer.remaining(self.clock.now()))
+    }
+
+    pub fn in_round(&self) -> bool {
+        self.in_round
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    /// Start a round with the next animal of the shuffled deck. A word
+    /// chain goes on from the last animal named, if the last round was won
+    /// and some animal can still follow it.
+    pub fn start_round(&mut self) -> Vec<GameEvent> {
+        if self.over {
+            return Vec::new()
+        }
+        if self.config.kind != GameKind::Chain {
+            let index = self.deck.draw();
+            return self.start_round_with(index)
+        }
+        match self.chain_start() {
+            Some(index) => self.start_round_with(index),
+            None => {
+                self.over = true;
+                vec![GameEvent::GameOver]
+            }
+        }
+    }
+
+    // Word chain: the animal to follow next round, if any animal of the
+    // pack can follow another.
+    fn chain_start(&mut self) -> Option<usize> {
+        let locale = self.config.catalog.locale().to_string();
+        if let Some(&last) = self.chain.last() {
+            if self.win_streak > 0 && !chain_answers(&self.animals, &locale, last, &self.chain).is_empty() {
+                return Some(last)
+            }
+        }
+        self.chain.clear();
+        for _ in 0..self.animals.len() {
+            let index = self.deck.draw();
+            if !chain_answers(&self.animals, &locale, index, &[]).is_empty() {
+                return Some(index)
+            }
+        }
+        None
+    }
+
+    /// Start a round with the animal at `index` as the secret. In a word
+    /// chain, `index` is the animal to follow instead.
+    pub fn start_round_with(&mut self, index: usize) -> Vec<GameEvent> {
+        let now = self.clock.now();
+        if self.blitz_timer.is_some_and(|timer| timer.expired(now)) || self.score <= 0 {
+            self.over = true;
+            return vec![GameEvent::GameOver]
+        }
+        if self.blitz_timer.is_none() {
+            self.blitz_timer = self.config.blitz_time.map(|limit| Countdown::start(now, limit));
+        }
+        self.round_timer = self.config.round_time.map(|limit| Countdown::start(now, limit));
+        self.guess_count = 0;
+        self.hints.clear();
+        let locale = self.config.catalog.locale();
+        let mut index = index;
+        if self.config.kind == GameKind::Chain {
+            if self.chain.last() != Some(&index) {
+                self.chain = vec![index];
+            }
+            index = chain_answers(&self.animals, locale, index, &self.chain).first().cloned().unwrap_or(index);
+        }
+        let animal = &self.animals[index];
+        let answer = if self.config.kind == GameKind::Quiz { &animal.r#type } else { animal.local_name(locale) };
+        self.round = Round::new(index, answer, self.config.mode.reveals_first_letters());
+        if self.config.kind == GameKind::Anagram {
+            self.anagram = scramble(animal.local_name(locale), &mut self.hint_rng);
+        }
+        self.in_round = true;
+        debug!("Pick {} as secret animal", self.secret_animal().name);
+        vec![
+            GameEvent::RoundStarted { word_len: self.word_len() },
+            GameEvent::HintUpdated(self.hint()),
+        ]
+    }
+
+    /// Check the timers. Call it regularly while waiting for input.
+    pub fn tick(&mut self) -> Vec<GameEvent> {
+        if self.over || !self.in_round {
+            return Vec::new()
+        }
+        let now = self.clock.now();
+        let message = self.answer_message("time-up");
+        if self.blitz_timer.is_some_and(|timer| timer.expired(now)) {
+            self.in_round = false;
+            self.over = true;
+            let mut events = vec![GameEvent::RoundLost(message)];
+            events.extend(self.picture());
+            events.push(GameEvent::GameOver);
+            return events
+        }
+        if self.round_timer.is_some_and(|timer| timer.expired(now)) {
+            return self.lose_round(message)
+        }
+        Vec::new()
+    }
+
+    /// Consume one line of player input: a command or a guess.
+    pub fn handle_input(&mut self, input: &str) -> Vec<GameEvent> {
+        let events = self.tick();
+        if self.over || !self.in_round {
+            return events
+        }
+        match check_game_command(input) {
+            GameCommand::Next => {
+                let next_msg = self.config.catalog.text("command-next").to_string();
+                self.lose_round(next_msg)
+            }
+            GameCommand::Quit => {
+                self.in_round = false;
+                self.over = true;
+                vec![GameEvent::Quit(self.config.catalog.text("command-quit").to_string())]
+            }
+            GameCommand::Scores => vec![GameEvent::ShowScores],
+            GameCommand::Hint => self.take_hint(),
+            GameCommand::PlayerInput(player_input) => {
+                let mut events = vec![GameEvent::GuessSubmitted(player_input.clone())];
+                events.extend(self.submit_guess(&player_input));
+                events
+            }
+        }
+    }
+
+    fn submit_guess(&mut self, guess: &str) -> Vec<GameEvent> {
+        let hangman = self.config.kind == GameKind::Hangman;
+        if hangman {
+            let letters: Vec<char> = guess.trim().nfc().collect();
+            if let [letter] = letters[..] {
+                return self.guess_letter(letter)
+            }
+        }
+        let checked = match self.config.kind {
+            GameKind::Chain => self.check_chain_guess(guess),
+            GameKind::Quiz => check_class_guess(guess, self.secret_animal(), &animal_classes(&self.animals), &self.config.catalog),
+            _ => check_animal_guess(guess, self.secret_animal(), &self.dictionary, &self.config.catalog),
+        };
+        match checked {
+            Guess::Right(right_msg) => {
+                if self.config.kind == GameKind::Chain {
+                    self.chain.extend(find_animal(&self.animals, guess));
+                }
+                // Hangman rewards finding the name before all its letters.
+                let word_bonus = if hangman { self.round.hidden_letters().len() as i64 * WORD_BONUS_PER_LETTER } else { 0 };
+                self.win_round(right_msg, word_bonus)
+            }
+            Guess::Wrong { message: wrong_msg, feedback, .. } => {
+                let matched = if hangman {
+                    self.round.remember_guess(guess, &feedback, &wrong_msg);
+                    0
+                } else {
+                    self.round.record_wrong_guess(guess, &feedback, &wrong_msg)
+                };
+                self.guess_count += 1; // only count guess if guess is valid.
+                let mut events = vec![
+                    GameEvent::GuessWrong { message: wrong_msg, matched, feedback },
+                    GameEvent::HintUpdated(self.hint()),
+                ];
+                events.extend(self.check_guesses_left());
+                events
+            }
+            Guess::Invalid(invalid_msg) => vec![GameEvent::GuessRejected(invalid_msg)],
+        }
+    }
+
+    // Word chain: any animal of the pack may follow, if it starts with the
+    // right letter and isn't in the chain yet.
+    fn check_chain_guess(&self, guess: &str) -> Guess {
+        let catalog = &self.config.catalog;
+        if !is_valid_name_input(guess) {
+            return Guess::Invalid(catalog.text("guess-invalid").to_string())
+        }
+        let locale = catalog.locale();
+        let after = match self.chain.last() {
+            Some(&after) => after,
+            None => return Guess::Invalid(catalog.text("guess-invalid").to_string()),
+        };
+        let index = match find_animal(&self.animals, guess) {
+            Some(index) => index,
+            None => {
+                let unknown_words = self.dictionary.unknown_words(&normalize_name(guess));
+                if !unknown_words.is_empty() {
+                    return Guess::Invalid(catalog.format("guess-unknown-words", &[("words", &unknown_words.join(", "))]))
+                }
+                return Guess::Wrong { message: catalog.text("chain-not-animal").to_string(), feedback: Vec::new(), close: false }
+            }
+        };
+        let name = self.animals[index].local_name(locale);
+        if chain_answers(&self.animals, locale, after, &self.chain).contains(&index) {
+            return Guess::Right(catalog.format("chain-right", &[("animal", &name), ("length", &(self.chain.len() + 1))]))
+        }
+        let message = if self.chain.contains(&index) {
+            catalog.format("chain-used", &[("animal", &name)])
+        } else {
+            let letter = chain_letter(self.animals[after].local_name(locale)).unwrap_or(' ').to_uppercase().to_string();
+            catalog.format("chain-wrong-letter", &[("animal", &name), ("letter", &letter)])
+        };
+        Guess::Wrong { message, feedback: Vec::new(), close: false }
+    }
+
+    // Hangman: reveal a letter, or count a miss.
+    fn guess_letter(&mut self, letter: char) -> Vec<GameEvent> {
+        let catalog = self.config.catalog.clone();
+        let shown = letter.to_uppercase().to_string();
+        if !letter.is_alphabetic() {
+            return vec![GameEvent::GuessRejected(catalog.text("guess-invalid").to_string())]
+        }
+        let found = match self.round.guess_letter(letter) {
+            Some(found) => found,
+            None => return vec![GameEvent::GuessRejected(catalog.format("hangman-letter-again", &[("letter", &shown)]))],
+        };
+        if found == 0 {
+            self.guess_count += 1;
+            let mut events = vec![GameEvent::LetterGuessed { message: catalog.format("hangman-letter-missing", &[("letter", &shown)]), found }];
+            events.extend(self.check_guesses_left());
+            return events
+        }
+        let mut events = vec![
+            GameEvent::LetterGuessed { message: catalog.format("hangman-letter-found", &[("letter", &shown), ("count", &found)]), found },
+            GameEvent::HintUpdated(self.hint()),
+        ];
+        if self.round.hidden_letters().is_empty() {
+            let message = catalog.format("guess-right", &[("animal", &self.secret_name())]);
+            events.extend(self.win_round(message, 0));
+        }
+        events
+    }
+
+    fn win_round(&mut self, message: String, word_bonus: i64) -> Vec<GameEvent> {
+        self.in_round = false;
+        self.win_streak += 1;
+        let mut round_score = RoundScore::new(self.config.mode, self.guess_count, self.max_guesses(), self.round_time_left(), self.win_streak, self.hints.len());
+        round_score.word_bonus = word_bonus;
+        self.score += round_score.total();
+        let mut events = vec![GameEvent::RoundWon(message)];
+        events.extend(self.picture());
+        events.push(GameEvent::RoundScored(round_score));
+        events.push(GameEvent::ScoreChanged(self.score));
+        events
+    }
+
+    // Message of a lost round that tells the answer, `round-{reason}`, or
+    // `chain-{reason}` and `quiz-{reason}` in those games.
+    fn answer_message(&self, reason: &str) -> String {
+        let catalog = &self.config.catalog;
+        let prefix = match self.config.kind {
+            GameKind::Chain => "chain",
+            GameKind::Quiz => "quiz",
+            _ => "round",
+        };
+        catalog.format(&format!("{}-{}", prefix, reason), &[("animal", &self.secret_name()), ("class", &self.secret_animal().r#type)])
+    }
+
+    // Lose the round once the last guess is used up.
+    fn check_guesses_left(&mut self) -> Vec<GameEvent> {
+        if self.guesses_left() > 0 {
+            return Vec::new()
+        }
+        let message = self.answer_message("out-of-guesses");
+        self.lose_round(message)
+    }
+
+    // Give the next hint tier, paid from the score. A hint never costs the
+    // last point, running out of points is for losing rounds.
+    fn take_hint(&mut self) -> Vec<GameEvent> {
+        let catalog = self.config.catalog.clone();
+        let tier = match self.next_hint() {
+            Some(tier) => tier,
+            None => return vec![GameEvent::GuessRejected(catalog.text("hint-none-left").to_string())],
+        };
+        let cost = tier.cost();
+        let tier_name = catalog.text(tier.message_key());
+        if self.score <= cost {
+            return vec![GameEvent::GuessRejected(catalog.format("hint-too-expensive", &[("hint", &tier_name), ("cost", &cost)]))]
+        }
+        self.score -= cost;
+        self.hints.push(tier);
+        let animal = self.secret_animal().clone();
+        let hint = match tier {
+            HintTier::Class => catalog.format("hint-class", &[("class", &animal.r#type)]),
+            tier if tier.is_fact() => catalog.format("hint-fact", &[("hint", &tier_name), ("fact", &tier.fact(&animal).unwrap_or_default())]),
+            HintTier::FirstLetter => {
+                self.round.reveal_first_letters();
+                catalog.text("hint-first-letters").to_string()
+            }
+            HintTier::RandomLetter => {
+                let hidden = self.round.hidden_letters();
+                let index = hidden[self.hint_rng.gen_range(0, hidden.len())];
+                catalog.format("hint-random-letter", &[("letter", &self.round.reveal_letter(index))])
+            }
+            HintTier::Picture => catalog.text("hint-picture").to_string(),
+            _ => catalog.format("hint-features", &[("features", &animal.features)]),
+        };
+        let mut events = vec![GameEvent::HintGiven { hint, cost }];
+        if tier == HintTier::Picture {
+            events.extend(self.picture());
+        }
+        events.push(GameEvent::HintUpdated(self.hint()));
+        events.push(GameEvent::ScoreChanged(self.score));
+        events
+    }
+
+    // Shown when the round ends, if the animal has a picture on disk.
+    fn picture(&self) -> Option<GameEvent> {
+        find_image(self.secret_animal()).map(|path| GameEvent::ShowPicture(path.to_string_lossy().into_owned()))
+    }
+
+    fn lose_round(&mut self, message: String) -> Vec<GameEvent> {
+        self.in_round = false;
+        self.win_streak = 0;
+        self.score -= LOST_ROUND_PENALTY;
+        let mut events = vec![GameEvent::RoundLost(message)];
+        events.extend(self.picture());
+        events.push(GameEvent::ScoreChanged(self.score));
+        if self.score <= 0 {
+            self.over = true;
+            events.push(GameEvent::GameOver);
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::clock::ManualClock;
+    use crate::mode::GameMode;
+    use crate::scoring::{FIRST_TRY_BONUS, SECONDS_PER_TIME_POINT};
+
+    fn test_animals() -> Vec<Animal> {
+        vec![
+            Animal::new("Aardvark", "Mammalia", "Long, sticky tongue"),
+            Animal::new("Adelie Penguin", "Aves", "White eye ring"),
+        ]
+    }
+
+    #[test]
+    fn check_guess_ignores_case_and_hyphens() {
+        let dictionary = Dictionary::for_animals(&test_animals(), "en");
+        assert!(matches!(check_guess("aardvark", "Aardvark", &dictionary, &Catalog::english()), Guess::Right(_)));
+        assert!(matches!(check_guess("african bush-elephant", "African Bush Elephant", &dictionary, &Catalog::english()), Guess::Right(_)));
+        assert!(matches!(check_guess("X Ray Tetra", "X-Ray Tetra", &dictionary, &Catalog::english()), Guess::Right(_)));
+        assert!(matches!(check_guess("aardwolf", "Aardvark", &dictionary, &Catalog::english()), Guess::Wrong { close: false, .. }));
+        assert!(matches!(check_guess("penguin", "Aardvark", &dictionary, &Catalog::english()), Guess::Invalid(_)));
+        assert!(matches!(check_guess("aardv4rk", "Aardvark", &dictionary, &Catalog::english()), Guess::Invalid(_)));
+    }
+
+    #[test]
+    fn aliases_are_right_answers() {
+        let mut orca = Animal::new("Killer Whale", "Mammalia", "");
+        orca.aliases.push("Orca".to_string());
+        let dictionary = Dictionary::for_animals(&[orca.clone()], "en");
+        assert!(matches!(check_animal_guess("orca", &orca, &dictionary, &Catalog::english()), Guess::Right(_)));
+        assert!(matches!(check_animal_guess("killer-whale", &orca, &dictionary, &Catalog::english()), Guess::Right(_)));
+        assert!(matches!(check_animal_guess("orcas", &orca, &dictionary, &Catalog::english()), Guess::Invalid(_)));
+    }
+
+    #[test]
+    fn sessions_use_the_names_of_their_language() {
+        let mut animals = test_animals();
+        animals[0].translations.insert("fr".to_string(), "Oryctérope".to_string());
+        let french = Arc::new(Catalog::builtin("fr").unwrap());
+        let mut session = GameSession::new(animals, GameConfig { catalog: french, ..GameConfig::default() });
+        session.start_round_with(0);
+        assert_eq!(session.hint(), "----------");
+        let events = session.handle_input("orycterope");
+        assert_eq!(events[1], GameEvent::RoundWon("Bravo ! L'animal secret est : Oryctérope !".to_string()));
+        session.start_round_with(0);
+        assert!(matches!(&session.handle_input("Aardvark")[1], GameEvent::RoundWon(message) if message.contains("aussi appelé Aardvark")));
+    }
+
+    #[test]
+    fn near_misses_are_very_close() {
+        let dictionary = Dictionary::for_animals(&test_animals(), "en");
+        assert!(matches!(check_guess("aardvak", "Aardvark", &dictionary, &Catalog::english()), Guess::Wrong { close: true, .. }));
+        assert!(matches!(check_guess("adelie penguim", "Adelie Penguin", &dictionary, &Catalog::english()), Guess::Wrong { close: true, .. }));
+    }
+
+    #[test]
+    fn nonsense_words_do_not_cost_a_guess() {
+        let mut session = GameSession::new(test_animals(), GameConfig::default());
+        session.start_round_with(0);
+        let events = session.handle_input("qwzxkvbt");
+        assert_eq!(events[1], GameEvent::GuessRejected("Input invalid. We don't know these words: qwzxkvbt".to_string()));
+        assert_eq!(session.guess_count(), 0);
+    }
+
+    #[test]
+    fn wrong_guess_reveals_matching_letters() {
+        let mut session = GameSession::new(test_animals(), GameConfig::default());
+        let events = session.start_round_with(1);
+        assert_eq!(events[1], GameEvent::HintUpdated("------ -------".to_string()));
+        let events = session.handle_input("Adelie Pelican");
+        assert!(matches!(events[1], GameEvent::GuessWrong { matched: 9, .. }));
+        assert_eq!(session.hint(), "Adelie Pe----n");
+        assert_eq!(session.guess_count(), 1);
+        assert_eq!(session.history()[0].guess, "Adelie Pelican");
+        assert!(session.in_round());
+    }
+
+    #[test]
+    fn scripted_round_scores_remaining_guesses() {
+        let mut session = GameSession::new(test_animals(), GameConfig::default());
+        session.start_round_with(0);
+        session.handle_input("aardwolf");
+        session.handle_input("aard");
+        let events = session.handle_input("AARDVARK");
+        assert_eq!(events.last(), Some(&GameEvent::ScoreChanged(START_SCORE + (MAX_GUESS_COUNT - 1) * 2)));
+        assert!(!session.in_round());
+    }
+
+    #[test]
+    fn win_streaks_raise_the_points() {
+        let mut session = GameSession::new(test_animals(), GameConfig::default());
+        for _ in 0..2 {
+            session.start_round_with(0);
+            session.handle_input("aardwolf");
+            session.handle_input("aardvark");
+        }
+        assert_eq!(session.win_streak(), 2);
+        assert_eq!(session.score(), START_SCORE + 11 * 2 + 11 * 125 / 100 * 2);
+        session.start_round_with(1);
+        session.handle_input("next");
+        assert_eq!(session.win_streak(), 0);
+    }
+
+    #[test]
+    fn hangman_reveals_letters_and_rewards_whole_names() {
+        let config = GameConfig { kind: GameKind::Hangman, ..GameConfig::default() };
+        let mut session = GameSession::new(test_animals(), config);
+        session.start_round_with(0);
+        assert!(matches!(session.handle_input("a")[1], GameEvent::LetterGuessed { found: 3, .. }));
+        assert_eq!(session.hint(), "Aa---a--");
+        assert!(matches!(session.handle_input("A")[1], GameEvent::GuessRejected(_)));
+        session.handle_input("z");
+        session.handle_input("aardwolf");
+        assert_eq!(session.hint(), "Aa---a--");
+        assert_eq!(session.guesses_left(), GameMode::Medium.max_misses() - 2);
+        session.handle_input("aardvark");
+        assert_eq!(session.score(), START_SCORE + (GameMode::Medium.max_misses() - 2 + 5) * 2);
+    }
+
+    #[test]
+    fn word_chains_go_on_while_rounds_are_won() {
+        let mut animals = test_animals();
+        animals.push(Animal::new("Kiwi", "Aves", "Nostrils at the tip of its beak"));
+        animals.push(Animal::new("Ibis", "Aves", "Long curved bill"));
+        let config = GameConfig { kind: GameKind::Chain, ..GameConfig::default() };
+        let mut session = GameSession::new(animals, config);
+        session.start_round_with(0);
+        assert_eq!(session.secret_animal().name, "Kiwi");
+        assert!(session.puzzle().unwrap().contains("starting with 'K'"));
+        assert!(matches!(&session.handle_input("ibis")[1], GameEvent::GuessWrong { message, .. } if message.contains("'K'")));
+        assert!(matches!(session.handle_input("aardvark")[1], GameEvent::GuessWrong { .. }));
+        assert!(matches!(session.handle_input("kiwi")[1], GameEvent::RoundWon(_)));
+        session.start_round();
+        assert_eq!(session.chain(), &[0, 2][..]);
+        assert!(matches!(session.handle_input("ibis")[1], GameEvent::RoundWon(_)));
+        // Nothing but the aardvark, already used, starts with 's'.
+        session.start_round();
+        assert_eq!(session.chain().len(), 1);
+    }
+
+    #[test]
+    fn quiz_asks_for_the_class() {
+        let config = GameConfig { kind: GameKind::Quiz, mode: GameMode::Easy, ..GameConfig::default() };
+        let mut session = GameSession::new(test_animals(), config);
+        session.start_round_with(1);
+        assert_eq!(session.visible_type(), None);
+        assert_eq!(session.hint(), "A---");
+        assert!(matches!(session.handle_input("reptiles")[1], GameEvent::GuessRejected(_)));
+        assert!(matches!(session.handle_input("mammals")[1], GameEvent::GuessWrong { .. }));
+        assert!(matches!(session.handle_input("birds")[1], GameEvent::RoundWon(_)));
+        session.start_round_with(0);
+        session.handle_input("aves");
+        session.handle_input("aves");
+        let events = session.handle_input("aves");
+        assert!(matches!(&events[3], GameEvent::RoundLost(message) if message.contains("Mammalia")));
+    }
+
+    #[test]
+    fn easy_mode_reveals_first_letters() {
+        let mut session = GameSession::new(test_animals(), GameConfig { mode: GameMode::Easy, ..GameConfig::default() });
+        session.start_round_with(1);
+        assert_eq!(session.hint(), "A----- P------");
+        assert_eq!(session.visible_type(), Some("Aves"));
+    }
+
+    #[test]
+    fn hard_mode_hides_hints_and_limits_guesses() {
+        let mut session = GameSession::new(test_animals(), GameConfig { mode: GameMode::Hard, ..GameConfig::default() });
+        session.start_round_with(0);
+        assert_eq!(session.visible_type(), None);
+        assert_eq!(session.visible_features(), None);
+        for _ in 0..GameMode::Hard.max_guess_count() - 1 {
+            session.handle_input("aardwolf");
+        }
+        let events = session.handle_input("aardwolf");
+        assert!(matches!(events[3], GameEvent::RoundLost(_)));
+        assert_eq!(session.score(), START_SCORE - 1);
+    }
+
+    #[test]
+    fn hints_escalate_and_cost_points() {
+        let mut animals = test_animals();
+        animals[1].habitat = Some("Antarctic coasts".to_string());
+        let mut session = GameSession::new(animals, GameConfig::default());
+        session.start_round_with(1);
+        assert_eq!(session.next_hint(), Some(HintTier::Class));
+        session.handle_input("hint");
+        assert_eq!(session.visible_type(), Some("Aves"));
+        let events = session.handle_input("hint");
+        assert_eq!(events[0], GameEvent::HintGiven { hint: "Its habitat: Antarctic coasts.".to_string(), cost: 1 });
+        session.handle_input("hint");
+        assert_eq!(session.hint(), "A----- P------");
+        session.handle_input("hint");
+        assert_eq!(session.round.hidden_letters().len(), 10);
+        // Medium already shows the features: no hint left.
+        assert_eq!(session.next_hint(), None);
+        assert_eq!(session.score(), START_SCORE - 6);
+        assert!(matches!(session.handle_input("hint")[0], GameEvent::GuessRejected(_)));
+    }
+
+    #[test]
+    fn skipping_rounds_ends_the_game() {
+        let mut session = GameSession::new(test_animals(), GameConfig::default());
+        for _ in 0..START_SCORE - 1 {
+            session.start_round_with(0);
+            session.handle_input("next");
+        }
+        session.start_round_with(0);
+        let events = session.handle_input("next");
+        assert_eq!(events.last(), Some(&GameEvent::GameOver));
+        assert!(session.is_over());
+        assert!(session.handle_input("aardvark").is_empty());
+    }
+
+    #[test]
+    fn time_left_adds_to_the_score() {
+        let clock = Rc::new(ManualClock::new());
+        let config = GameConfig { round_time: Some(Duration::from_secs(60)), ..GameConfig::default() };
+        let mut session = GameSession::with_clock(test_animals(), config, clock.clone());
+        session.start_round_with(0);
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(session.round_time_left(), Some(Duration::from_secs(40)));
+        session.handle_input("aardvark");
+        assert_eq!(session.score(), START_SCORE + (MAX_GUESS_COUNT + 40 / SECONDS_PER_TIME_POINT as i64 + FIRST_TRY_BONUS) * 2);
+    }
+
+    #[test]
+    fn round_timer_runs_out() {
+        let clock = Rc::new(ManualClock::new());
+        let config = GameConfig { round_time: Some(Duration::from_secs(30)), ..GameConfig::default() };
+        let mut session = GameSession::with_clock(test_animals(), config, clock.clone());
+        session.start_round_with(0);
+        assert!(session.tick().is_empty());
+        clock.advance(Duration::from_secs(31));
+        let events = session.handle_input("aardvark");
+        assert!(matches!(events[0], GameEvent::RoundLost(_)));
+        assert_eq!(session.score(), START_SCORE - 1);
+    }
+
+    #[test]
+    fn blitz_ends_the_session() {
+        let clock = Rc::new(ManualClock::new());
+        let config = GameConfig { blitz_time: Some(Duration::from_secs(90)), ..GameConfig::default() };
+        let mut session = GameSession::with_clock(test_animals(), config, clock.clone());
+        session.start_round_with(0);
+        clock.advance(Duration::from_secs(50));
+        session.handle_input("aardvark");
+        session.start_round_with(1);
+        assert_eq!(session.blitz_time_left(), Some(Duration::from_secs(40)));
+        clock.advance(Duration::from_secs(40));
+        assert_eq!(session.tick().last(), Some(&GameEvent::GameOver));
+        assert!(session.is_over());
+    }
+
+    #[test]
+    fn seeded_sessions_pick_the_same_animals() {
+        let config = GameConfig { seed: Some(2019), ..GameConfig::default() };
+        let mut session1 = GameSession::new(test_animals(), config.clone());
+        let mut session2 = GameSession::new(test_animals(), config);
+        for _ in 0..4 {
+            session1.start_round();
+            session2.start_round();
+            assert_eq!(session1.secret_animal().name, session2.secret_animal().name);
+            session1.handle_input("next");
+            session2.handle_input("next");
+        }
+    }
+}
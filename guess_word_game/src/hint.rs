@@ -0,0 +1,76 @@
+use crate::animal::Animal;
+
+/// What the `hint` command gives away, from the cheapest to the most
+/// telling. Each tier is given once a round, and tiers the animal data or
+/// the difficulty can't use are skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HintTier {
+    /// The taxonomic class, the `type` of the animal.
+    Class,
+    // Facts only given for animals that have them in the database.
+    Habitat,
+    Continent,
+    Diet,
+    ConservationStatus,
+    /// First letter of each word of the name.
+    FirstLetter,
+    /// One hidden letter picked at random.
+    RandomLetter,
+    Features,
+    /// Only for animals with a picture on disk.
+    Picture,
+}
+
+impl HintTier {
+    pub const ALL: [HintTier; 9] = [
+        HintTier::Class,
+        HintTier::Habitat,
+        HintTier::Continent,
+        HintTier::Diet,
+        HintTier::ConservationStatus,
+        HintTier::FirstLetter,
+        HintTier::RandomLetter,
+        HintTier::Features,
+        HintTier::Picture,
+    ];
+
+    /// Points taken from the score when the hint is given.
+    pub fn cost(self) -> i64 {
+        match self {
+            HintTier::Class | HintTier::Habitat | HintTier::Continent | HintTier::Diet | HintTier::ConservationStatus => 1,
+            HintTier::FirstLetter | HintTier::RandomLetter => 2,
+            HintTier::Features => 3,
+            HintTier::Picture => 4,
+        }
+    }
+
+    /// Catalog key of the tier name.
+    pub fn message_key(self) -> &'static str {
+        match self {
+            HintTier::Class => "tier-class",
+            HintTier::Habitat => "tier-habitat",
+            HintTier::Continent => "tier-continent",
+            HintTier::Diet => "tier-diet",
+            HintTier::ConservationStatus => "tier-conservation-status",
+            HintTier::FirstLetter => "tier-first-letters",
+            HintTier::RandomLetter => "tier-random-letter",
+            HintTier::Features => "tier-features",
+            HintTier::Picture => "tier-picture",
+        }
+    }
+
+    /// The optional database field this tier gives away, if it is one.
+    pub fn fact(self, animal: &Animal) -> Option<&str> {
+        match self {
+            HintTier::Habitat => animal.fact("habitat"),
+            HintTier::Continent => animal.fact("continent"),
+            HintTier::Diet => animal.fact("diet"),
+            HintTier::ConservationStatus => animal.fact("conservation_status"),
+            _ => None,
+        }
+    }
+
+    pub fn is_fact(self) -> bool {
+        matches!(self, HintTier::Habitat | HintTier::Continent | HintTier::Diet | HintTier::ConservationStatus)
+    }
+}
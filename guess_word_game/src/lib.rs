@@ -0,0 +1,78 @@
+// Author: Nguyen Phi Hung
+// App: Guess the animal name game - headless game engine
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate serde_derive;
+
+mod animal;
+pub use animal::*;
+
+mod deck;
+pub use deck::*;
+
+mod game;
+pub use game::*;
+
+mod round;
+pub use round::*;
+
+mod scoring;
+pub use scoring::*;
+
+mod achievement;
+pub use achievement::*;
+
+mod hint;
+pub use hint::*;
+
+mod clock;
+pub use clock::*;
+
+mod mode;
+pub use mode::*;
+
+mod locale;
+pub use locale::*;
+
+mod matching;
+pub use matching::*;
+
+mod dictionary;
+pub use dictionary::*;
+
+mod pack;
+pub use pack::*;
+
+mod builder;
+pub use builder::*;
+
+mod record;
+pub use record::*;
+
+mod reverse;
+pub use reverse::*;
+
+mod puzzle;
+pub use puzzle::*;
+
+mod filter;
+pub use filter::*;
+
+mod cli;
+pub use cli::*;
+
+mod profile;
+pub use profile::*;
+
+mod view;
+pub use view::*;
+
+mod preview;
+pub use preview::*;
+
+mod multiplayer;
+pub use multiplayer::*;
+
+mod network;
+pub use network::*;
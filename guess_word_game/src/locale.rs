@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Message catalogs compiled into the game, as (language code, TOML text).
+const BUILTIN_CATALOGS: [(&str, &str); 3] = [
+    ("en", include_str!("locales/en.toml")),
+    ("fr", include_str!("locales/fr.toml")),
+    ("vi", include_str!("locales/vi.toml")),
+];
+
+/// Player-facing messages of one language, keyed like `guess-wrong`.
+///
+/// Messages are TOML strings where `{name}` stands for an argument. Keys a
+/// catalog leaves out fall back to the English message.
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    locale: String,
+    messages: BTreeMap<String, String>,
+}
+
+fn parse_messages(text: &str) -> Result<BTreeMap<String, String>, String> {
+    toml::from_str(text).map_err(|error| error.to_string())
+}
+
+/// Language of a locale name: "fr" for "fr_FR.UTF-8" or "fr-FR".
+pub fn language_code(locale: &str) -> String {
+    locale.split(['_', '-', '.', '@']).next().unwrap_or("").to_lowercase()
+}
+
+/// Language of the environment, from LC_ALL, LC_MESSAGES or LANG.
+pub fn system_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .map(|locale| language_code(&locale))
+        .find(|code| !code.is_empty() && code != "c" && code != "posix")
+}
+
+impl Catalog {
+    /// Language codes of the compiled-in catalogs.
+    pub fn builtin_locales() -> Vec<&'static str> {
+        BUILTIN_CATALOGS.iter().map(|(code, _)| *code).collect()
+    }
+
+    pub fn english() -> Catalog {
+        Catalog::builtin(DEFAULT_LOCALE).unwrap_or_else(|| panic!("The English catalog is missing"))
+    }
+
+    /// The compiled-in catalog of a language, if there is one.
+    pub fn builtin(locale: &str) -> Option<Catalog> {
+        let code = language_code(locale);
+        let (_, text) = BUILTIN_CATALOGS.iter().find(|(builtin, _)| *builtin == code)?;
+        let messages = parse_messages(text).unwrap_or_else(|error| panic!("Something wrong when parsing the {} catalog {}", code, error));
+        if code == DEFAULT_LOCALE {
+            return Some(Catalog { locale: code, messages })
+        }
+        Some(Catalog::english().overlay(code, messages))
+    }
+
+    /// The compiled-in catalog of a language, or the English messages under
+    /// its code, so animal names still follow the language.
+    pub fn for_locale(locale: &str) -> Catalog {
+        Catalog::builtin(locale).unwrap_or_else(|| Catalog::english().overlay(language_code(locale), BTreeMap::new()))
+    }
+
+    /// Load a catalog from a TOML file, named after the file: `de.toml`
+    /// holds the "de" messages.
+    pub fn from_file(path: &Path) -> Result<Catalog, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        let messages = parse_messages(&text).map_err(|error| format!("{}: {}", path.display(), error))?;
+        let code = path.file_stem().and_then(|stem| stem.to_str()).map(language_code).unwrap_or_default();
+        Ok(Catalog::english().overlay(code, messages))
+    }
+
+    /// A compiled-in catalog by language code, or a catalog file by path.
+    pub fn load(lang: &str) -> Result<Catalog, String> {
+        if lang.to_lowercase().ends_with(".toml") {
+            return Catalog::from_file(Path::new(lang))
+        }
+        Catalog::builtin(lang).ok_or_else(|| {
+            format!("no messages for '{}', expected one of {} or a .toml catalog", lang, Catalog::builtin_locales().join(", "))
+        })
+    }
+
+    // Replace the messages this catalog has, keep the others.
+    fn overlay(mut self, locale: String, messages: BTreeMap<String, String>) -> Catalog {
+        self.messages.extend(messages);
+        self.locale = locale;
+        self
+    }
+
+    /// Language code of the catalog, e.g. "fr".
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Fingerprint of the messages, to tell whether two catalogs say the
+    /// same: a 64-bit FNV-1a hash of the keys and messages, in hex.
+    pub fn version(&self) -> String {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for (key, message) in &self.messages {
+            for byte in key.bytes().chain(Some(0)).chain(message.bytes()).chain(Some(0)) {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        format!("{:016x}", hash)
+    }
+
+    /// The message of `key`, or the key itself if no catalog has it.
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        match self.messages.get(key) {
+            Some(message) => message,
+            None => {
+                warn!("No message for {} in the {} catalog", key, self.locale);
+                key
+            }
+        }
+    }
+
+    /// The message of `key` with its `{name}` arguments filled in.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        let mut message = self.text(key).to_string();
+        for (name, value) in args {
+            message = message.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        message
+    }
+}
+
+impl Default for Catalog {
+    fn default() -> Catalog {
+        Catalog::english()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `{name}` arguments of a message, sorted.
+    fn arguments(message: &str) -> Vec<&str> {
+        let mut arguments: Vec<&str> = message.split('{').skip(1).filter_map(|part| part.split('}').next()).collect();
+        arguments.sort_unstable();
+        arguments
+    }
+
+    #[test]
+    fn builtin_catalogs_translate_every_message() {
+        let (_, english) = BUILTIN_CATALOGS[0];
+        let english = parse_messages(english).unwrap();
+        for (code, text) in BUILTIN_CATALOGS.iter() {
+            let messages = parse_messages(text).unwrap();
+            for (key, message) in &english {
+                let translated = messages.get(key).unwrap_or_else(|| panic!("{} has no {}", code, key));
+                assert_eq!(arguments(translated), arguments(message), "{} {}", code, key);
+            }
+        }
+    }
+
+    #[test]
+    fn catalogs_fill_arguments_and_fall_back_to_english() {
+        let french = Catalog::builtin("fr_FR.UTF-8").unwrap();
+        assert_eq!(french.locale(), "fr");
+        assert_eq!(french.format("guess-right", &[("animal", &"Oryctérope")]), "Bravo ! L'animal secret est : Oryctérope !");
+        let partial = Catalog::english().overlay("de".to_string(), parse_messages("guess-wrong = \"Falsch!\"").unwrap());
+        assert_eq!(partial.text("guess-wrong"), "Falsch!");
+        assert_eq!(partial.text("guess-close"), "You're very close!");
+        assert!(Catalog::builtin("xx").is_none());
+        assert_eq!(language_code("pt-BR"), "pt");
+    }
+
+    #[test]
+    fn catalogs_with_other_messages_have_another_version() {
+        assert_eq!(Catalog::english().version(), Catalog::for_locale("en_US").version());
+        let german = Catalog::english().overlay("de".to_string(), parse_messages("guess-wrong = \"Falsch!\"").unwrap());
+        assert_ne!(german.version(), Catalog::english().version());
+        assert_ne!(Catalog::for_locale("fr").version(), Catalog::english().version());
+    }
+}
@@ -1,239 +1,663 @@
 // Author: Nguyen Phi Hung
 // App: Guess the animal name game
-// TODO: Check if the guess is nonsence word
 #[macro_use]
 extern crate log;
-#[macro_use]
-extern crate serde_derive;
-// extern crate serde;
-// extern crate serde_json;
-// extern crate env_logger;
-// extern crate regex;
-// extern crate colored;
-
-use crossterm::{Crossterm, ClearType, Colored, Color, Colorize, Styler, Attribute};
-use std::io;
-use rand::Rng;
-use regex::Regex;
-use std::{thread, time};
-// use std::cmp::Ordering;
 
-// Define Enum
-enum Guess {
-    Right(String),
-    Wrong(String),
-    Invalid(String)
-}
+mod tui;
 
-enum GameCommand {
-    PlayerInput(String),
-    Next(String),
-    Quit(String),
-}
-
-enum GameMode {
-    Easy
-    Medium
-    Hard
-}
+use crossterm::{Colored, Color, Colorize, Styler, Attribute};
+use std::io;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::{thread, time};
+use std::time::SystemTime;
+use guess_word_game::{Achievement, Animal, AnimalFilter, Answer, Catalog, Client, Clock, GameCommand, GameConfig, GameEvent, GameKind, GameMode, GameSession, LogAction, MAX_QUESTIONS, MultiplayerGame, PACK_USAGE, PackBuilder, PackError, Picture, PlayerScore, PlayerSetup, Question, RemoteView, Reveal, ReverseGame, ReverseStep, RoundRecord, ScoreBoard, ServerMessage, SessionLog, SessionRecorder, SystemClock, TurnOrder, USAGE, check_game_command, daily_seed, days_since_epoch, host_game, load_animal_data, load_pack, merge_packs, parse_args, parse_pack_args, round_score_text, standings_text, system_language, validate_animal, write_pack};
+use tui::Tui;
 
-// struct GameController {
-//     PlayerInput: String,
-//     Next: "next",
-//     Quit: "quit",
-// }
 // Define Constant
 // const ONE_SEC: time::Duration = time::Duration::from_secs(1);
 // const TEN_MILIS: time::Duration = time::Duration::from_millis(100);
-
-// Define Struct
-#[derive(Debug)]
-struct GameConfig {
-    mode: GameMode,
-    input: GameCommand,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Animal {
-    name: String,
-    r#type: String, // r# to avoid calling standard method instead of define.
-    features: String,
-}
+const FRAME_TIME: time::Duration = time::Duration::from_millis(30);
+const BANNER_WIDTH: usize = 32;
 
 // Define Functions
 
-fn read_console_input(mut input_str: &mut String) {
-    io::stdin().read_line(&mut input_str)
+fn read_console_input(input_str: &mut String) {
+    let read = io::stdin().read_line(input_str)
         .expect("Failed to read console_input!");
+    // The prompts ask again on empty input, which would never end once
+    // the input is closed.
+    if read == 0 {
+        process::exit(0);
+    }
     let trim_input = String::from(input_str.trim());
     input_str.clear();
     input_str.push_str(&trim_input);
 }
 
+// Title centred in a line of dashes, e.g. "-----Guess the Animal Game------".
+fn banner_line(title: &str) -> String {
+    let width = BANNER_WIDTH.max(title.chars().count() + 10);
+    let left = (width - title.chars().count()) / 2;
+    let right = width - left - title.chars().count();
+    format!("{}{}{}", "-".repeat(left), title, "-".repeat(right))
+}
 
-fn pick_random_index(slice_len: usize) -> usize {
-    rand::thread_rng().gen_range(0, slice_len-1)
+fn choose_game_mode(catalog: &Catalog) -> GameMode {
+    println!("{}", catalog.text("choose-mode").blue().on_white().underlined());
+    for (index, mode) in GameMode::ALL.iter().enumerate() {
+        println!("{}. {} - {}", index + 1, mode.local_name(catalog), mode.local_description(catalog));
+    }
+    loop {
+        let mut playerinput = String::new();
+        read_console_input(&mut playerinput);
+        if let Some(mode) = GameMode::from_name(&playerinput) {
+            return mode
+        }
+        println!("{}{}{}", Colored::Fg(Color::Red), Attribute::Bold, catalog.text("choose-mode-again"));
+    }
 }
 
-fn check_game_command(gameinput: &str) -> GameCommand {
-    if gameinput.eq_ignore_ascii_case("next") {
-        return GameCommand::Next("Session Skip!".to_string())
+// Keep asking until the filter leaves some animals, an empty line keeps them all.
+fn choose_animal_filter(animal_data: &[Animal], catalog: &Catalog) -> AnimalFilter {
+    println!("{}", catalog.text("choose-filter").blue().on_white().underlined());
+    loop {
+        let mut playerinput = String::new();
+        read_console_input(&mut playerinput);
+        let matching = AnimalFilter::parse(&playerinput)
+            .and_then(|filter| filter.apply(animal_data.to_vec()).map(|_| filter));
+        match matching {
+            Ok(filter) => return filter,
+            Err(error) => println!("{}{}{}", Colored::Fg(Color::Red), Attribute::Bold, catalog.format("choose-filter-again", &[("error", &error)])),
+        }
     }
-    if gameinput.eq_ignore_ascii_case("quit") {
-        return GameCommand::Quit("Game Exit!".to_string())
+}
+
+fn render_event(tui: &mut Tui, event: &GameEvent) {
+    let catalog = tui.catalog();
+    match event {
+        GameEvent::RoundStarted { .. } => tui.push_message(Color::White, catalog.text("round-started")),
+        GameEvent::RoundWon(right_msg) => tui.push_message(Color::Blue, &format!("*********{}*********", right_msg)),
+        GameEvent::GuessWrong { message, matched, .. } => {
+            tui.push_message(Color::Blue, &catalog.format("guess-matched", &[("message", message), ("matched", matched)]));
+        }
+        GameEvent::GuessRejected(invalid_msg) => tui.push_message(Color::Red, invalid_msg),
+        GameEvent::LetterGuessed { message, found } => tui.push_message(if *found > 0 { Color::Green } else { Color::Red }, message),
+        GameEvent::RoundLost(next_msg) | GameEvent::Quit(next_msg) => tui.push_message(Color::Magenta, next_msg),
+        GameEvent::GameOver => tui.push_message(Color::Red, catalog.text("game-over")),
+        GameEvent::HintGiven { hint, cost } => tui.push_message(Color::Green, &catalog.format("hint-given", &[("cost", cost), ("hint", hint)])),
+        GameEvent::TurnChanged(player) => tui.push_message(Color::Yellow, &catalog.format("turn-changed", &[("player", player)])),
+        GameEvent::PlayerPassed(player) => tui.push_message(Color::Magenta, &catalog.format("player-passed", &[("player", player)])),
+        GameEvent::PlayerLeft(player) => tui.push_message(Color::Magenta, &catalog.format("player-left", &[("player", player)])),
+        GameEvent::PlayerScored { player, score } => tui.push_message(Color::Yellow, &catalog.format("player-scored", &[("player", player), ("score", score)])),
+        GameEvent::RoundScored(score) => tui.push_message(Color::Yellow, &round_score_text(score, &catalog)),
+        GameEvent::ShowPicture(path) => {
+            let (columns, rows) = tui.picture_area();
+            match Picture::load(Path::new(path), columns, rows) {
+                Ok(picture) => tui.show_picture(picture),
+                Err(error) => warn!("Failed to load the picture {}: {}", path, error),
+            }
+        }
+        GameEvent::GuessSubmitted(_) | GameEvent::HintUpdated(_) | GameEvent::ScoreChanged(_) | GameEvent::ShowScores => {}
     }
-    GameCommand::PlayerInput(gameinput.to_string())
 }
 
-fn check_guess(guess: &str, answer: &str, thinking_duration: time::Duration) -> Guess {
-    let result_validator = Regex::new(&(r"^(?i)".to_owned() + &answer)).unwrap();
-    let input_validator = Regex::new(r"^([A-Za-z-]+\s*)+$").unwrap();
-    thread::sleep(thinking_duration);
-    if guess.len() != answer.len() {
-        return Guess::Invalid(format!("Input invalid. Your guess word's length must be {} characters long with spaces!", answer.len()).to_string())
+fn ask_player_name(catalog: &Catalog) -> String {
+    println!("{}", catalog.text("ask-name").blue().on_white().underlined());
+    let mut playerinput = String::new();
+    read_console_input(&mut playerinput);
+    if playerinput.is_empty() {
+        return catalog.text("default-player").to_string()
     }
-    println!(".");
-    thread::sleep(thinking_duration);
-    if !input_validator.is_match(&guess) {
-        return Guess::Invalid("Input invalid. Your guess word should contain only word with space!".to_string())
+    playerinput
+}
+
+fn profile_summary(score_board: &ScoreBoard, player: &str, catalog: &Catalog) -> Option<String> {
+    let profile = score_board.profile(player)?;
+    Some(catalog.format("profile-summary", &[
+        ("player", &profile.name),
+        ("rounds", &profile.rounds_played),
+        ("won", &profile.rounds_won),
+        ("guesses", &format!("{:.1}", profile.average_guesses())),
+        ("streak", &profile.longest_streak),
+    ]))
+}
+
+fn render_scores(score_board: &ScoreBoard, player: &str, kind: GameKind, mode: GameMode, catalog: &Catalog) {
+    let title = catalog.format("high-scores", &[("game", &kind.local_name(catalog)), ("mode", &mode.local_name(catalog))]);
+    println!("{}{}{}", Colored::Fg(Color::Yellow), Attribute::Bold, title);
+    for (rank, high_score) in score_board.top_scores(kind, mode).iter().enumerate() {
+        println!("{:>2}. {:<20} {}", rank + 1, high_score.player, high_score.score);
     }
-    
-    println!("..");
-    thread::sleep(thinking_duration);
-    if !result_validator.is_match(&guess) {
-        return Guess::Wrong("You guessed wrong!".to_string())
+    if let Some(summary) = profile_summary(score_board, player, catalog) {
+        println!("\n{}", summary);
     }
-    println!("...");
-    thread::sleep(thinking_duration);
-    Guess::Right(format!("Congratulation! The secret animal is the {}!", answer).to_string())
 }
 
-fn extract_matching_char(str1: &str, str2: &str) -> Vec<(usize, char)> {
-    let mut matched_chars = Vec::new();
-    for (char_index, (str1_char, str2_char)) in str1.chars().zip(str2.chars()).enumerate() {
-        if str1_char.eq_ignore_ascii_case(&str2_char) {
-            matched_chars.push((char_index, str1_char))
-        }
+// The message panel only has a few lines, so the table is squeezed onto one.
+fn show_scores(tui: &mut Tui, score_board: &ScoreBoard, player: &str, kind: GameKind, mode: GameMode) {
+    let catalog = tui.catalog();
+    let table: Vec<String> = score_board.top_scores(kind, mode).iter().take(5).enumerate()
+        .map(|(rank, high_score)| format!("{}. {} {}", rank + 1, high_score.player, high_score.score))
+        .collect();
+    let title = catalog.format("high-scores", &[("game", &kind.local_name(&catalog)), ("mode", &mode.local_name(&catalog))]);
+    tui.push_message(Color::Yellow, &format!("{}: {}", title, table.join("  ")));
+    if let Some(summary) = profile_summary(score_board, player, &catalog) {
+        tui.push_message(Color::Yellow, &summary);
     }
-    // matched_chars.dedup(); // remove element duplication
-    matched_chars
 }
 
-fn generate_animal_data() -> Vec<Animal> {
-    let animal_data_string = include_str!("animal_datas.json");
-    let animal_data: Vec<Animal> = serde_json::from_str(&animal_data_string).unwrap_or_else(|error| {
-        panic!("Something wrong when parsing data {:?}", error)
-    });
-    animal_data
+fn save_scores(score_board: &ScoreBoard, scores_path: &Path) {
+    if let Err(error) = score_board.save(scores_path) {
+        warn!("Failed to save scores to {}: {}", scores_path.display(), error);
+    }
 }
 
+// Returns the achievements the event unlocked.
+fn record_event(event: &GameEvent, session: &GameSession, score_board: &mut ScoreBoard, player: &str, scores_path: &Path) -> Vec<Achievement> {
+    let won = match event {
+        GameEvent::RoundWon(_) => true,
+        GameEvent::RoundLost(_) => false,
+        _ => return Vec::new(),
+    };
+    let guesses = session.guess_count() as u64 + if won { 1 } else { 0 };
+    score_board.record_round(player, won, guesses);
+    let round = RoundRecord {
+        won,
+        guess_count: session.guess_count(),
+        hints: session.hints_taken().len(),
+        class: &session.secret_animal().r#type,
+        mode: session.config().mode,
+        score: session.score(),
+    };
+    let unlocked = score_board.record_achievements(player, &round);
+    save_scores(score_board, scores_path);
+    unlocked
+}
 
-fn gameloop() {
-    // Initialize Terminal
-    let cterm = Crossterm::new();
-    let terminal = cterm.terminal();
-    let term_color = cterm.color();
-    terminal.clear(ClearType::All).unwrap();
-    env_logger::init();
+fn render_achievements(score_board: &ScoreBoard, player: &str, catalog: &Catalog) {
+    let progress = match score_board.profile(player) {
+        Some(profile) => &profile.achievements,
+        None => return,
+    };
+    let heading = catalog.format("achievements", &[("unlocked", &progress.unlocked.len()), ("total", &Achievement::ALL.len())]);
+    println!("\n{}{}{}", Colored::Fg(Color::Yellow), Attribute::Bold, heading);
+    for achievement in Achievement::ALL.iter() {
+        let (mark, color) = if progress.is_unlocked(*achievement) { ("*", Color::Green) } else { ("-", Color::DarkGrey) };
+        println!("{}{} {:<16} {}", Colored::Fg(color), mark, catalog.text(achievement.message_key()), catalog.text(achievement.description_key()));
+    }
+}
 
+fn log_step(recorder: &mut Option<SessionRecorder>, at: time::Duration, action: LogAction, session: &GameSession, events: &[GameEvent]) {
+    if let Some(recorder) = recorder {
+        if let Err(error) = recorder.record(at, action, session, events) {
+            warn!("Failed to save the session log: {}", error);
+        }
+    }
+}
+
+fn play_input(session: &mut GameSession, recorder: &mut Option<SessionRecorder>, at: time::Duration, input: &str) -> Vec<GameEvent> {
+    let events = session.handle_input(input);
+    log_step(recorder, at, LogAction::Input(input.to_string()), session, &events);
+    events
+}
+
+fn gameloop(animal_data: Vec<Animal>, config: GameConfig, player: &str, scores_path: &Path, anticipate_time: time::Duration, record_path: Option<&Path>) {
     // Initialize Game Variable
-    let animal_data = generate_animal_data();
-    let mut score: i64 = 10;
-    let MaxGuessCount = 12;
-    let mut guess_count: i64;
-    let anticipate_time = time::Duration::from_millis(500);
     debug!("Animal Database: {:?}", animal_data);
     debug!("Result anticipate duration: {:?}", anticipate_time);
+    let mut score_board = ScoreBoard::load(scores_path).unwrap_or_else(|error| {
+        warn!("Failed to load scores from {}: {}", scores_path.display(), error);
+        ScoreBoard::default()
+    });
+    let mode = config.mode;
+    let kind = config.kind;
+    let catalog = config.catalog.clone();
+    let clock = Rc::new(SystemClock::new());
+    let mut session = GameSession::with_clock(animal_data, config, clock.clone());
+    info!("Session seed: {}", session.seed());
+    let mut recorder = record_path.map(|path| SessionRecorder::new(&session, path));
+
+    // Initialize Terminal
+    let mut tui = Tui::new(catalog.clone()).unwrap_or_else(|error| {
+        eprintln!("Failed to open the game screen: {}", error);
+        process::exit(1);
+    });
+    tui.push_message(Color::DarkGrey, &catalog.format("intro-seed", &[("seed", &session.seed())]));
+    tui.push_message(Color::White, catalog.text("intro-solo"));
+    if let Some(intro) = session.config().kind.intro_key() {
+        tui.push_message(Color::White, catalog.text(intro));
+    }
     // Main game loop
-    loop {
-        term_color.reset().unwrap();
-        if score <= 0 {
-            println!("Game Over!");
-            break;
-        }
-        guess_count = 0;
-        let secret_animal = &animal_data[pick_random_index(animal_data.len())];
-        let mut guess_hint = Vec::new();
-        let mut word_len = 0;
-        for char_ in secret_animal.name.chars() {
-            if char_.is_whitespace() {
-                guess_hint.push(' ')
-            } else {
-                word_len += 1;
-                guess_hint.push('-')
-            }
+    while !session.is_over() {
+        let events = session.start_round();
+        log_step(&mut recorder, clock.now(), LogAction::StartRound, &session, &events);
+        for event in events {
+            render_event(&mut tui, &event);
         }
-
-        println!("{}", "Generate a secret animal name...\n".italic());
-        // println!("Pick {} as secret animal", secret_animal.name);
-        // Hint
-        // Session game loop
-        loop {
-            term_color.reset().unwrap();
-            let guess_hint_str: String = guess_hint.iter().collect();
-            let style_hint = format!("{} [{}]", guess_hint_str, word_len);
-            println!("Score: {}.\n", score);
-            if guess_count>0 {
-                println!("Guess count: {} times.\n", guess_count);
+        // Session game loop: never blocks, so timers and the reveal keep running.
+        let mut reveal: Option<Reveal> = None;
+        // The picture of the last animal stays up until the first input.
+        let mut fresh_round = true;
+        while session.in_round() {
+            let mut events = session.tick();
+            log_step(&mut recorder, clock.now(), LogAction::Tick, &session, &events);
+            match reveal.take() {
+                Some(current) if current.is_done(clock.now()) => {
+                    events.extend(play_input(&mut session, &mut recorder, clock.now(), &current.input));
+                }
+                Some(current) => {
+                    tui.set_thinking(Some(current.dots(clock.now())));
+                    reveal = Some(current);
+                }
+                None => if let Some(playerinput) = tui.poll_line() {
+                    if fresh_round {
+                        tui.clear_picture();
+                        fresh_round = false;
+                    }
+                    match check_game_command(&playerinput) {
+                        GameCommand::PlayerInput(_) if anticipate_time > time::Duration::from_millis(0) => {
+                            tui.push_message(Color::White, &catalog.format("your-guess", &[("guess", &playerinput)]));
+                            reveal = Some(Reveal::new(playerinput, clock.now(), anticipate_time));
+                        }
+                        _ => events.extend(play_input(&mut session, &mut recorder, clock.now(), &playerinput)),
+                    }
+                }
+            }
+            if reveal.is_none() {
+                tui.set_thinking(None);
             }
-            println!("{}{}This animal's name has {} characters.", Colored::Fg(Color::Yellow), Attribute::Bold, word_len);
-            println!("It belong to {} family.", secret_animal.r#type);
-            println!("Its features are: {}.", secret_animal.features);
-            println!("What is it?\n");
-            println!("{}", "Type-in your guess:".blue().on_white().underlined());
-            println!("{}", "You can type 'next' to skip or 'quit' to end the game.".blue().on_white().underlined());
-
-            println!("{}{}", Colored::Fg(Color::Green), style_hint);
-            let mut playerinput = String::new();
-            read_console_input(&mut playerinput);
-            match check_game_command(&playerinput) {
-                GameCommand::Next(next_msg)=> {
-                    println!("{}", next_msg);
-                    score -= 1;
-                    break;
+            for event in events {
+                render_event(&mut tui, &event);
+                for achievement in record_event(&event, &session, &mut score_board, player, scores_path) {
+                    let name = catalog.text(achievement.message_key());
+                    tui.push_message(Color::Green, &catalog.format("achievement-unlocked", &[("name", &name)]));
                 }
-                GameCommand::Quit(quit_msg)=> {
-                    println!("{}", quit_msg);
-                    std::process::exit(0);
+                if event == GameEvent::ShowScores {
+                    show_scores(&mut tui, &score_board, player, kind, mode);
                 }
-                GameCommand::PlayerInput(player_input)=> {
-                    println!("\nYour guess is {}\n", player_input);
+            }
+            tui.refresh(&session, player);
+            thread::sleep(FRAME_TIME);
+        }
+    }
+    drop(tui);
+    if let Some(recorder) = &recorder {
+        if let Err(error) = recorder.save() {
+            warn!("Failed to save the session log: {}", error);
+        }
+    }
+
+    println!("{}{}{}", Colored::Fg(Color::White), Attribute::Bold, catalog.format("final-score", &[("score", &session.score())]));
+    if let Some(rank) = score_board.add_high_score(player, kind, mode, session.score()) {
+        let message = catalog.format("new-high-score", &[("rank", &rank), ("game", &kind.local_name(&catalog)), ("mode", &mode.local_name(&catalog))]);
+        println!("{}{}{}", Colored::Fg(Color::Yellow), Attribute::Bold, message);
+    }
+    save_scores(&score_board, scores_path);
+    render_scores(&score_board, player, kind, mode, &catalog);
+    render_achievements(&score_board, player, &catalog);
+}
+
+fn render_standings(scores: &[PlayerScore], catalog: &Catalog) {
+    let mut scores = scores.to_vec();
+    scores.sort_by_key(|score| std::cmp::Reverse(score.score));
+    println!("{}{}{}", Colored::Fg(Color::White), Attribute::Bold, catalog.text("final-scores"));
+    for (rank, score) in scores.iter().enumerate() {
+        println!("{:>2}. {:<20} {}", rank + 1, score.name, score.score);
+    }
+    let winners: Vec<&str> = scores.iter()
+        .filter(|score| scores.first().is_some_and(|best| best.score == score.score))
+        .map(|score| score.name.as_str())
+        .collect();
+    match winners.as_slice() {
+        [winner] => println!("{}{}{}", Colored::Fg(Color::Yellow), Attribute::Bold, catalog.format("winner", &[("player", winner)])),
+        winners => println!("{}{}{}", Colored::Fg(Color::Yellow), Attribute::Bold, catalog.format("tie", &[("players", &winners.join(", "))])),
+    }
+}
+
+fn hot_seat_loop(animal_data: Vec<Animal>, config: GameConfig, names: Vec<String>, rounds: u32) {
+    let catalog = config.catalog.clone();
+    let mut game = MultiplayerGame::new(animal_data, config, names, TurnOrder::HotSeat, rounds).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(2);
+    });
+    let mut tui = Tui::new(catalog.clone()).unwrap_or_else(|error| {
+        eprintln!("Failed to open the game screen: {}", error);
+        process::exit(1);
+    });
+    tui.push_message(Color::DarkGrey, &catalog.format("intro-seed", &[("seed", &game.seed())]));
+    tui.push_message(Color::White, catalog.text("intro-hot-seat"));
+    while !game.is_over() {
+        for event in game.start_round() {
+            render_event(&mut tui, &event);
+        }
+        while game.in_round() {
+            if let Some(playerinput) = tui.poll_line() {
+                for event in game.handle_input(game.turn(), &playerinput) {
+                    render_event(&mut tui, &event);
+                    if event == GameEvent::ShowScores {
+                        tui.push_message(Color::Yellow, &standings_text(&game.scores()));
+                    }
                 }
             }
-            match check_guess(&playerinput, &secret_animal.name, anticipate_time) {
-                Guess::Right(right_msg)=> {
-                    println!("{}{}*********{}*********", Colored::Fg(Color::Blue), Attribute::Bold, right_msg);
-                    println!("\n{}{}{}\n", Colored::Fg(Color::White), Attribute::Bold, "#".repeat(100));
-                    score += MaxGuessCount-guess_count;
-                    break;
-                },
-                Guess::Wrong(wrong_msg)=> {
-                    println!("{}{}", Colored::Fg(Color::Blue), wrong_msg);
-                    let char_matched: Vec<(usize, char)> = extract_matching_char(&secret_animal.name, &playerinput);
-                    // println!("Char Matched: {:?}", char_matched);
-
-                    println!("There are {} characters in your guess that match the secret animal!", char_matched.len());
-                    for (char_index, match_char) in char_matched {
-                        guess_hint[char_index] = match_char
+            tui.refresh(&game, "");
+            thread::sleep(FRAME_TIME);
+        }
+    }
+    drop(tui);
+    render_standings(&game.scores(), &catalog);
+}
+
+// Events of other players are shown with their name, and their rejected
+// guesses are none of our business.
+fn render_remote_events(tui: &mut Tui, view: &RemoteView, player: Option<&str>, events: &[GameEvent]) {
+    let other = player.filter(|name| *name != view.name());
+    let catalog = tui.catalog();
+    for event in events {
+        match (event, other) {
+            (GameEvent::GuessSubmitted(guess), Some(name)) => {
+                tui.push_message(Color::White, &catalog.format("player-guess", &[("player", &name), ("guess", guess)]));
+            }
+            (GameEvent::GuessWrong { message, .. }, Some(name)) => tui.push_message(Color::Blue, &format!("{}: {}", name, message)),
+            (GameEvent::GuessRejected(_), Some(_)) | (GameEvent::ShowScores, Some(_)) => {}
+            (GameEvent::ShowScores, None) => tui.push_message(Color::Yellow, &standings_text(view.scores())),
+            _ => render_event(tui, event),
+        }
+    }
+}
+
+fn network_loop(mut client: Client, player: &str, catalog: Arc<Catalog>) {
+    let mut view = RemoteView::new(catalog.clone());
+    let mut tui = Tui::new(catalog.clone()).unwrap_or_else(|error| {
+        eprintln!("Failed to open the game screen: {}", error);
+        process::exit(1);
+    });
+    tui.push_message(Color::White, catalog.text("waiting-players"));
+    while !view.is_over() {
+        if let Some(playerinput) = tui.poll_line() {
+            if let Err(error) = client.send_input(&playerinput) {
+                tui.push_message(Color::Red, &catalog.format("lost-host", &[("error", &error)]));
+                break
+            }
+        }
+        match client.try_receive() {
+            Ok(Some(message)) => {
+                view.apply(&message);
+                match &message {
+                    ServerMessage::Welcome { players, .. } => {
+                        tui.push_message(Color::White, &catalog.format("intro-race", &[("players", &players.join(", "))]));
                     }
-                    guess_count += 1; // only count guess if guess is valid.
-                },
-                Guess::Invalid(invalid_msg)=> {
-                    println!("{}{}{}", Colored::Fg(Color::Red), Attribute::Bold, invalid_msg);
+                    ServerMessage::Events { player, events } => render_remote_events(&mut tui, &view, player.as_deref(), events),
+                    ServerMessage::RoundStarted { .. } => {}
                 }
             }
-            println!("\n{}{}{}\n", Colored::Fg(Color::White), Attribute::Bold, "#".repeat(100));
+            Ok(None) => {}
+            Err(error) => {
+                warn!("Lost the host: {}", error);
+                break
+            }
+        }
+        let name = if view.name().is_empty() { player } else { view.name() };
+        tui.refresh(&view, name);
+        thread::sleep(FRAME_TIME);
+    }
+    drop(tui);
+    render_standings(view.scores(), &catalog);
+}
+
+// The `pack` subcommand: build a word pack from local source files.
+fn build_pack<I: IntoIterator<Item = String>>(args: I) {
+    let options = parse_pack_args(args).unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, PACK_USAGE);
+        process::exit(2);
+    });
+    let mut builder = PackBuilder::new();
+    for source in &options.sources {
+        builder.add_source(source).unwrap_or_else(|error| {
+            eprintln!("Failed to read source: {}", error);
+            process::exit(1);
+        });
+    }
+    for reason in &builder.skipped {
+        eprintln!("Skipped {}", reason);
+    }
+    let duplicates = builder.duplicates;
+    let skipped = builder.skipped.len();
+    let animals = builder.finish();
+    let count = animals.len();
+    write_pack(animals, &options.output).unwrap_or_else(|error| {
+        eprintln!("Failed to write word pack: {}", error);
+        process::exit(1);
+    });
+    println!("Wrote {} animals to {} ({} duplicates merged, {} entries skipped).", count, options.output.display(), duplicates, skipped);
+}
+
+// Ask until the answer is yes, no or "?".
+fn ask_answer(prompt: &str, catalog: &Catalog) -> Answer {
+    loop {
+        println!("{}", prompt.blue());
+        let mut input = String::new();
+        read_console_input(&mut input);
+        match Answer::parse(&input, catalog) {
+            Some(answer) => return answer,
+            None => println!("{}", catalog.text("answer-help").red()),
         }
     }
 }
 
-// Main 
+fn ask_text(prompt: &str) -> String {
+    println!("{}", prompt.blue());
+    let mut input = String::new();
+    read_console_input(&mut input);
+    input
+}
+
+// After a lost reverse game: find out the animal, and learn it if the
+// pack didn't have it. Returns the learned animal.
+fn reverse_learn(game: &ReverseGame, learned_path: &Path, catalog: &Catalog) -> Option<Animal> {
+    let name = ask_text(catalog.text("reverse-ask-name"));
+    if name.is_empty() {
+        return None
+    }
+    if let Some(known) = game.find(&name) {
+        println!("{}", catalog.format("reverse-known", &[("animal", &known.local_name(catalog.locale()))]));
+        for (question, answer) in game.mismatches(known) {
+            let answer = catalog.text(if *answer == Answer::Yes { "answer-yes" } else { "answer-no" });
+            println!("{}", catalog.format("reverse-mismatch", &[("question", &question.text(catalog)), ("answer", &answer)]));
+        }
+        return None
+    }
+    let knows_class = game.answers().iter().any(|(question, answer)| matches!(question, Question::Class(_)) && *answer == Answer::Yes);
+    let class = if knows_class { String::new() } else { ask_text(catalog.text("reverse-ask-class")) };
+    let features = ask_text(catalog.text("reverse-ask-features"));
+    let animal = game.learn(&name, &class, &features);
+    let invalid = |message| PackError::Invalid { path: learned_path.to_path_buf(), line: None, message };
+    let learned = validate_animal(&animal).map_err(invalid).and_then(|_| {
+        let known = if learned_path.exists() { load_pack(learned_path)? } else { Vec::new() };
+        let animals = merge_packs(vec![known, vec![animal.clone()]]);
+        let count = animals.len();
+        write_pack(animals, learned_path)?;
+        Ok(count)
+    });
+    match learned {
+        Ok(count) => {
+            println!("{}", catalog.format("reverse-learned", &[("animal", &animal.name), ("path", &learned_path.display()), ("count", &count)]).green());
+            Some(animal)
+        }
+        Err(error) => {
+            println!("{}", catalog.format("reverse-learn-failed", &[("error", &error)]).red());
+            None
+        }
+    }
+}
+
+fn reverse_loop(mut animal_data: Vec<Animal>, learned_path: &Path, catalog: &Catalog) {
+    println!("{}", catalog.format("reverse-intro", &[("questions", &MAX_QUESTIONS)]));
+    loop {
+        let mut game = ReverseGame::new(animal_data.clone());
+        loop {
+            match game.next_step() {
+                ReverseStep::Ask(question) => {
+                    let prompt = catalog.format("question-numbered", &[("number", &(game.answers().len() + 1)), ("question", &question.text(catalog))]);
+                    let answer = ask_answer(&prompt, catalog);
+                    game.answer(question, answer);
+                }
+                ReverseStep::Guess(index) => {
+                    let name = game.animals()[index].local_name(catalog.locale()).to_string();
+                    if ask_answer(&catalog.format("reverse-guess", &[("animal", &name)]), catalog) == Answer::Yes {
+                        println!("{}", catalog.format("reverse-won", &[("questions", &game.answers().len())]).green());
+                        break
+                    }
+                    game.reject_guess(index);
+                }
+                ReverseStep::GiveUp => {
+                    println!("{}", catalog.text("reverse-give-up").yellow());
+                    animal_data.extend(reverse_learn(&game, learned_path, catalog));
+                    break
+                }
+            }
+        }
+        if ask_answer(catalog.text("play-again"), catalog) != Answer::Yes {
+            break
+        }
+    }
+}
+
+fn replay_log<I: IntoIterator<Item = String>>(args: I) {
+    let (path, lang) = match args.into_iter().collect::<Vec<_>>().as_slice() {
+        [path] => (path.clone(), None),
+        [path, option, lang] if option == "--lang" => (path.clone(), Some(lang.clone())),
+        _ => {
+            eprintln!("replay expects the path of one session log\n\n{}", USAGE);
+            process::exit(2);
+        }
+    };
+    let log = SessionLog::load(Path::new(&path)).unwrap_or_else(|error| {
+        eprintln!("Failed to load session log {}: {}", path, error);
+        process::exit(1);
+    });
+    let catalog = match lang {
+        Some(lang) => Catalog::load(&lang),
+        None => Ok(Catalog::for_locale(&log.locale)),
+    };
+    let report = catalog.and_then(|catalog| log.replay_with(catalog)).unwrap_or_else(|error| {
+        eprintln!("Cannot replay {}: {}", path, error);
+        process::exit(1);
+    });
+    println!("{}", report);
+    if report.divergence.is_some() {
+        process::exit(1);
+    }
+}
+
 fn main() {
+    env_logger::init();
+    let mut args = std::env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("pack") => return build_pack(args.skip(1)),
+        Some("replay") => return replay_log(args.skip(1)),
+        _ => {}
+    }
+    let options = parse_args(args).unwrap_or_else(|error| {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(2);
+    });
+    let animal_data = load_animal_data(&options.db_paths).unwrap_or_else(|error| {
+        eprintln!("Failed to load animal database: {}", error);
+        process::exit(1);
+    });
+    let catalog = match &options.lang {
+        Some(lang) => Catalog::load(lang).unwrap_or_else(|error| {
+            eprintln!("Failed to load the messages: {}", error);
+            process::exit(1);
+        }),
+        None => system_language().and_then(|code| Catalog::builtin(&code)).unwrap_or_default(),
+    };
+    let catalog = Arc::new(catalog);
 
     // Render Game Title
-    println!("{}", "--------------------------------".yellow().on_magenta().negative().rapid_blink());
-    println!("{}", "-----Guess the Animal Game------".yellow().on_magenta().negative().rapid_blink());
-    println!("{}", "--------------------------------".yellow().on_magenta().negative().rapid_blink());
+    let banner = banner_line(catalog.text("banner"));
+    let dashes = "-".repeat(banner.chars().count());
+    println!("{}", dashes.as_str().yellow().on_magenta().negative().rapid_blink());
+    println!("{}", banner.as_str().yellow().on_magenta().negative().rapid_blink());
+    println!("{}", dashes.as_str().yellow().on_magenta().negative().rapid_blink());
+
+    if options.game == GameKind::Reverse {
+        // Animals learned in earlier games come on top of the database.
+        let learned = if options.learned_path.exists() {
+            load_pack(&options.learned_path).unwrap_or_else(|error| {
+                eprintln!("Failed to load learned animals: {}", error);
+                process::exit(1);
+            })
+        } else {
+            Vec::new()
+        };
+        let animal_data = options.filter.apply(merge_packs(vec![animal_data, learned])).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(2);
+        });
+        reverse_loop(animal_data, &options.learned_path, &catalog);
+        return
+    }
 
-    gameloop();
-}
\ No newline at end of file
+    if let PlayerSetup::Join(address) = &options.players {
+        let player = options.player.unwrap_or_else(|| ask_player_name(&catalog));
+        let client = Client::join(address.as_str(), &player).unwrap_or_else(|error| {
+            eprintln!("Failed to join {}: {}", address, error);
+            process::exit(1);
+        });
+        network_loop(client, &player, catalog);
+        return
+    }
+    let player = match &options.players {
+        PlayerSetup::HotSeat(_) => String::new(),
+        _ => options.player.unwrap_or_else(|| ask_player_name(&catalog)),
+    };
+    let (mode, filter) = match options.mode {
+        Some(mode) => (mode, options.filter),
+        None => {
+            let mode = choose_game_mode(&catalog);
+            let filter = if options.filter.is_empty() { choose_animal_filter(&animal_data, &catalog) } else { options.filter };
+            (mode, filter)
+        }
+    };
+    let animal_count = animal_data.len();
+    let animal_data = filter.apply(animal_data).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(2);
+    });
+    if !filter.is_empty() {
+        println!("{}", catalog.format("filter-matches", &[("count", &animal_data.len()), ("total", &animal_count), ("filter", &filter)]));
+    }
+    let seed = if options.daily {
+        Some(daily_seed(days_since_epoch(SystemTime::now())))
+    } else {
+        options.seed
+    };
+    let config = GameConfig { mode, round_time: options.round_time, blitz_time: options.blitz_time, seed, catalog: catalog.clone(), kind: options.game };
+    match options.players {
+        PlayerSetup::Solo | PlayerSetup::Join(_) => gameloop(animal_data, config, &player, &options.scores_path, options.anticipate_time, options.record_path.as_deref()),
+        PlayerSetup::HotSeat(names) => hot_seat_loop(animal_data, config, names, options.rounds),
+        PlayerSetup::Host { port, players } => {
+            let listener = TcpListener::bind(("127.0.0.1", port)).unwrap_or_else(|error| {
+                eprintln!("Failed to listen on port {}: {}", port, error);
+                process::exit(1);
+            });
+            let rounds = options.rounds;
+            // The host runs beside our own client and stops with the process.
+            thread::spawn(move || {
+                if let Err(error) = host_game(listener, animal_data, config, players, rounds) {
+                    error!("The game host stopped: {}", error);
+                }
+            });
+            let client = Client::join(("127.0.0.1", port), &player).unwrap_or_else(|error| {
+                eprintln!("Failed to join the game: {}", error);
+                process::exit(1);
+            });
+            network_loop(client, &player, catalog);
+        }
+    }
+}
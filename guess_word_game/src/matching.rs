@@ -0,0 +1,152 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+/// How one letter of a guess compares to the secret name.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LetterFeedback {
+    /// Right letter in the right position.
+    Correct(char),
+    /// The letter is in the name, somewhere else.
+    Present(char),
+    Absent(char),
+}
+
+impl LetterFeedback {
+    pub fn letter(self) -> char {
+        match self {
+            LetterFeedback::Correct(letter) | LetterFeedback::Present(letter) | LetterFeedback::Absent(letter) => letter,
+        }
+    }
+}
+
+/// Lowercase form of a letter without its accents: 'É' and 'e' compare
+/// equal. Only the Latin, Greek and Cyrillic accents are dropped, the
+/// vowel signs of other scripts are letters of their own.
+pub fn fold_letter(letter: char) -> char {
+    let mut base = None;
+    let mut accents_only = true;
+    decompose_canonical(letter, |part| match base {
+        None => base = Some(part),
+        Some(_) => accents_only &= is_accent(part),
+    });
+    let letter = match base {
+        Some(base) if accents_only => base,
+        _ => letter,
+    };
+    // Lowercasing only grows a few letters, like 'İ', keep their first char.
+    letter.to_lowercase().next().unwrap_or(letter)
+}
+
+/// Combining diacritical marks, the accents of the Latin, Greek and
+/// Cyrillic scripts.
+pub fn is_accent(c: char) -> bool {
+    ('\u{300}'..='\u{36f}').contains(&c)
+}
+
+/// Lowercase a name, drop its accents and treat hyphens as spaces, so
+/// "Bush-Éléphant" and "bush elephant" compare equal. Runs of whitespace
+/// collapse to one space.
+///
+/// Letters map one to one to the composed (NFC) form of the name, so
+/// positions in a normalised guess match the letters of the answer.
+pub fn normalize_name(name: &str) -> String {
+    let composed: String = name.nfc().collect();
+    let mut normalized = String::with_capacity(composed.len());
+    for word in composed.split(|c: char| c.is_whitespace() || c == '-').filter(|word| !word.is_empty()) {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.extend(word.chars().map(fold_letter));
+    }
+    normalized
+}
+
+/// Letters, spaces, hyphens and apostrophes are the only characters animal
+/// names use. Letters may be of any script and carry combining marks.
+pub fn is_valid_name_input(input: &str) -> bool {
+    !input.trim().is_empty() && input.chars().all(|c| c.is_alphabetic() || is_combining_mark(c) || c.is_whitespace() || c == '-' || c == '\'')
+}
+
+/// Edit distance between two strings, counted in chars.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Largest edit distance still considered "very close" for a name this long.
+pub fn close_distance(answer_len: usize) -> usize {
+    if answer_len <= 5 { 1 } else { 2 }
+}
+
+/// Wordle-style feedback for each character of `guess` against `answer`.
+/// Both should already be normalised. A letter is only reported as present
+/// as many times as it appears in the answer outside the correct positions.
+pub fn letter_feedback(guess: &str, answer: &str) -> Vec<LetterFeedback> {
+    let guess: Vec<char> = guess.chars().collect();
+    let answer: Vec<char> = answer.chars().collect();
+    let mut unmatched: Vec<char> = answer.iter().enumerate()
+        .filter(|(index, answer_char)| guess.get(*index) != Some(answer_char))
+        .map(|(_, answer_char)| *answer_char)
+        .collect();
+    guess.iter().enumerate().map(|(index, &guess_char)| {
+        if answer.get(index) == Some(&guess_char) {
+            return LetterFeedback::Correct(guess_char)
+        }
+        match unmatched.iter().position(|&answer_char| answer_char == guess_char && !guess_char.is_whitespace()) {
+            Some(found) => {
+                unmatched.remove(found);
+                LetterFeedback::Present(guess_char)
+            }
+            None => LetterFeedback::Absent(guess_char),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use LetterFeedback::*;
+
+    #[test]
+    fn normalize_ignores_case_and_hyphens() {
+        assert_eq!(normalize_name("african bush-elephant"), normalize_name("African Bush Elephant"));
+        assert_eq!(normalize_name("  Red   Panda "), "red panda");
+    }
+
+    #[test]
+    fn normalize_folds_accents_of_any_script() {
+        assert_eq!(normalize_name("Oryctérope"), "orycterope");
+        // Decomposed input: "e" followed by a combining acute accent.
+        assert_eq!(normalize_name("Orycte\u{301}rope"), "orycterope");
+        assert_eq!(normalize_name("ÑANDÚ"), "nandu");
+        assert_eq!(normalize_name("Бурый Медведь"), normalize_name("бурый медведь"));
+        assert_eq!(normalize_name("Ёж"), "еж");
+        assert_eq!(normalize_name("Hổ"), "ho");
+        // Devanagari vowel signs are kept.
+        assert_eq!(normalize_name("बाघ").chars().count(), 3);
+        assert!(is_valid_name_input("Orycte\u{301}rope"));
+        assert!(is_valid_name_input("बाघ"));
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("aardvark", "aardvark"), 0);
+        assert_eq!(levenshtein("aardvak", "aardvark"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn feedback_marks_repeated_letters_once() {
+        assert_eq!(letter_feedback("llama", "koala"), vec![Present('l'), Absent('l'), Correct('a'), Absent('m'), Correct('a')]);
+        assert_eq!(letter_feedback("emu", "emu"), vec![Correct('e'), Correct('m'), Correct('u')]);
+    }
+}
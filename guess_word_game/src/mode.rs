@@ -0,0 +1,185 @@
+use std::sync::Arc;
+use std::time::Duration;
+use crate::game::MAX_GUESS_COUNT;
+use crate::locale::Catalog;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GameMode {
+    Easy,
+    Medium,
+    Hard
+}
+
+/// Which game to play with the animals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum GameKind {
+    /// The player guesses the secret animal.
+    #[default]
+    Classic,
+    /// The computer guesses the player's animal with yes/no questions.
+    Reverse,
+    /// The player guesses the name letter by letter, with a few misses
+    /// allowed.
+    Hangman,
+    /// The player unscrambles the shuffled letters of the name.
+    Anagram,
+    /// The player names an animal starting with the last letter of the
+    /// previous one.
+    Chain,
+    /// The player tells the class of the animal shown.
+    Quiz,
+}
+
+// Define Struct
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    pub mode: GameMode,
+    /// Time limit of each round. Time left when the animal is found adds to the score.
+    pub round_time: Option<Duration>,
+    /// Blitz: the whole session ends when this runs out.
+    pub blitz_time: Option<Duration>,
+    /// Seed of the animal deck. A random one is picked when missing.
+    pub seed: Option<u64>,
+    /// Language of the messages and of the animal names.
+    pub catalog: Arc<Catalog>,
+    pub kind: GameKind,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Easy, GameMode::Medium, GameMode::Hard];
+
+    /// Parse a mode from its name or its menu number.
+    pub fn from_name(name: &str) -> Option<GameMode> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "easy" | "e" | "1" => Some(GameMode::Easy),
+            "medium" | "m" | "2" => Some(GameMode::Medium),
+            "hard" | "h" | "3" => Some(GameMode::Hard),
+            _ => None,
+        }
+    }
+
+    /// Name of the mode in the language of `catalog`.
+    pub fn local_name(self, catalog: &Catalog) -> &str {
+        catalog.text(match self {
+            GameMode::Easy => "mode-easy",
+            GameMode::Medium => "mode-medium",
+            GameMode::Hard => "mode-hard",
+        })
+    }
+
+    pub fn local_description(self, catalog: &Catalog) -> &str {
+        catalog.text(match self {
+            GameMode::Easy => "mode-easy-description",
+            GameMode::Medium => "mode-medium-description",
+            GameMode::Hard => "mode-hard-description",
+        })
+    }
+
+    /// Wrong guesses allowed before the round is lost.
+    pub fn max_guess_count(self) -> i64 {
+        match self {
+            GameMode::Hard => MAX_GUESS_COUNT / 2,
+            _ => MAX_GUESS_COUNT,
+        }
+    }
+
+    /// Hangman: wrong letters or names allowed before the round is lost.
+    pub fn max_misses(self) -> i64 {
+        match self {
+            GameMode::Easy => 8,
+            GameMode::Medium => 6,
+            GameMode::Hard => 4,
+        }
+    }
+
+    /// Class quiz: wrong classes allowed before the round is lost.
+    pub fn max_quiz_answers(self) -> i64 {
+        match self {
+            GameMode::Easy => 3,
+            GameMode::Medium => 2,
+            GameMode::Hard => 1,
+        }
+    }
+
+    pub fn score_multiplier(self) -> i64 {
+        match self {
+            GameMode::Easy => 1,
+            GameMode::Medium => 2,
+            GameMode::Hard => 3,
+        }
+    }
+
+    pub fn shows_type(self) -> bool {
+        self == GameMode::Easy
+    }
+
+    pub fn shows_features(self) -> bool {
+        self != GameMode::Hard
+    }
+
+    /// Whether the first letter of each word is revealed when a round starts.
+    pub fn reveals_first_letters(self) -> bool {
+        self == GameMode::Easy
+    }
+}
+
+impl GameKind {
+    pub const ALL: [GameKind; 6] = [GameKind::Classic, GameKind::Reverse, GameKind::Hangman, GameKind::Anagram, GameKind::Chain, GameKind::Quiz];
+
+    pub fn from_name(name: &str) -> Option<GameKind> {
+        GameKind::ALL.iter().cloned().find(|kind| kind.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameKind::Classic => "classic",
+            GameKind::Reverse => "reverse",
+            GameKind::Hangman => "hangman",
+            GameKind::Anagram => "anagram",
+            GameKind::Chain => "chain",
+            GameKind::Quiz => "quiz",
+        }
+    }
+
+    /// Name of the game in the language of `catalog`.
+    pub fn local_name(self, catalog: &Catalog) -> &str {
+        catalog.text(match self {
+            GameKind::Classic => "kind-classic",
+            GameKind::Reverse => "kind-reverse",
+            GameKind::Hangman => "kind-hangman",
+            GameKind::Anagram => "kind-anagram",
+            GameKind::Chain => "kind-chain",
+            GameKind::Quiz => "kind-quiz",
+        })
+    }
+
+    /// Catalog key of the rules shown when a solo session starts, beside
+    /// the classic ones.
+    pub fn intro_key(self) -> Option<&'static str> {
+        match self {
+            GameKind::Hangman => Some("intro-hangman"),
+            GameKind::Anagram => Some("intro-anagram"),
+            GameKind::Chain => Some("intro-chain"),
+            GameKind::Quiz => Some("intro-quiz"),
+            GameKind::Classic | GameKind::Reverse => None,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig { mode: GameMode::Medium, round_time: None, blitz_time: None, seed: None, catalog: Arc::new(Catalog::english()), kind: GameKind::Classic }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_from_name_or_menu_number() {
+        assert_eq!(GameMode::from_name("HARD"), Some(GameMode::Hard));
+        assert_eq!(GameMode::from_name(" 1 "), Some(GameMode::Easy));
+        assert_eq!(GameMode::from_name("extreme"), None);
+    }
+}
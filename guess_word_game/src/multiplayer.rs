@@ -0,0 +1,438 @@
+use crate::animal::Animal;
+use crate::deck::{AnimalDeck, random_seed};
+use crate::dictionary::Dictionary;
+use crate::game::{GameCommand, GameEvent, Guess, GuessRecord, check_animal_guess, check_game_command};
+use crate::mode::GameConfig;
+use crate::round::Round;
+use crate::scoring::RoundScore;
+use crate::view::{GameView, standings_text};
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 4;
+pub const DEFAULT_ROUNDS: u32 = 5;
+
+/// How the players of a multiplayer game share a round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TurnOrder {
+    /// Players take turns at the same keyboard.
+    HotSeat,
+    /// Everyone guesses at once, the first right guess wins the round.
+    Race,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerScore {
+    pub name: String,
+    pub score: i64,
+}
+
+struct Player {
+    name: String,
+    score: i64,
+    guess_count: i64,
+    /// Rounds won in a row.
+    win_streak: u64,
+    /// Left a race: no more guesses, the score stays in the standings.
+    left: bool,
+}
+
+/// Several players guessing the same secret animal, each with their own
+/// score and their own guesses per round, scored like solo rounds. Rounds
+/// are not timed.
+pub struct MultiplayerGame {
+    animals: Vec<Animal>,
+    dictionary: Dictionary,
+    config: GameConfig,
+    order: TurnOrder,
+    players: Vec<Player>,
+    turn: usize,
+    round: Round,
+    seed: u64,
+    deck: AnimalDeck,
+    rounds: u32,
+    rounds_played: u32,
+    in_round: bool,
+    over: bool,
+}
+
+impl MultiplayerGame {
+    pub fn new(animals: Vec<Animal>, config: GameConfig, names: Vec<String>, order: TurnOrder, rounds: u32) -> Result<MultiplayerGame, String> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&names.len()) {
+            return Err(format!("A multiplayer game needs {} to {} players", MIN_PLAYERS, MAX_PLAYERS))
+        }
+        for (index, name) in names.iter().enumerate() {
+            if names[..index].iter().any(|other| other.to_lowercase() == name.to_lowercase()) {
+                return Err(format!("Two players are called '{}'", name))
+            }
+        }
+        let seed = config.seed.unwrap_or_else(random_seed);
+        Ok(MultiplayerGame {
+            seed,
+            deck: AnimalDeck::new(animals.len(), seed),
+            dictionary: Dictionary::for_animals(&animals, config.catalog.locale()),
+            animals,
+            config,
+            order,
+            players: names.into_iter().map(|name| Player { name, score: 0, guess_count: 0, win_streak: 0, left: false }).collect(),
+            turn: 0,
+            round: Round::default(),
+            rounds,
+            rounds_played: 0,
+            in_round: false,
+            over: false,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn scores(&self) -> Vec<PlayerScore> {
+        self.players.iter().map(|player| PlayerScore { name: player.name.clone(), score: player.score }).collect()
+    }
+
+    /// Names of the players with the best score.
+    pub fn winners(&self) -> Vec<&str> {
+        let best = self.players.iter().map(|player| player.score).max().unwrap_or(0);
+        self.players.iter().filter(|player| player.score == best).map(|player| player.name.as_str()).collect()
+    }
+
+    /// Player whose turn it is, in a hot-seat round.
+    pub fn current_player(&self) -> Option<&str> {
+        if self.order == TurnOrder::HotSeat && self.in_round {
+            return Some(&self.players[self.turn].name)
+        }
+        None
+    }
+
+    /// Index of the player whose turn it is, in a hot-seat game.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// Number of the current round, counted from 1.
+    pub fn round_number(&self) -> u32 {
+        if self.in_round { self.rounds_played + 1 } else { self.rounds_played }
+    }
+
+    pub fn rounds(&self) -> u32 {
+        self.rounds
+    }
+
+    pub fn guesses_left(&self, player: usize) -> i64 {
+        if self.players[player].left {
+            return 0
+        }
+        self.config.mode.max_guess_count() - self.players[player].guess_count
+    }
+
+    pub fn secret_animal(&self) -> &Animal {
+        &self.animals[self.round.secret_index()]
+    }
+
+    /// Name of the secret animal in the language of the game.
+    pub fn secret_name(&self) -> &str {
+        self.secret_animal().local_name(self.config.catalog.locale())
+    }
+
+    pub fn visible_type(&self) -> Option<&str> {
+        if self.config.mode.shows_type() {
+            return Some(&self.secret_animal().r#type)
+        }
+        None
+    }
+
+    pub fn visible_features(&self) -> Option<&str> {
+        if self.config.mode.shows_features() {
+            return Some(&self.secret_animal().features)
+        }
+        None
+    }
+
+    pub fn in_round(&self) -> bool {
+        self.in_round
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    /// Start the next round, or end the game once every round was played.
+    pub fn start_round(&mut self) -> Vec<GameEvent> {
+        if self.over || self.in_round {
+            return Vec::new()
+        }
+        if self.rounds_played >= self.rounds {
+            self.over = true;
+            return vec![GameEvent::GameOver]
+        }
+        let index = self.deck.draw();
+        self.start_round_with(index)
+    }
+
+    /// Start a round with the animal at `index` as the secret.
+    pub fn start_round_with(&mut self, index: usize) -> Vec<GameEvent> {
+        for player in &mut self.players {
+            player.guess_count = 0;
+        }
+        let name = self.animals[index].local_name(self.config.catalog.locale());
+        self.round = Round::new(index, name, self.config.mode.reveals_first_letters());
+        self.in_round = true;
+        // The first turn moves around the table from one round to the next.
+        self.turn = self.rounds_played as usize % self.players.len();
+        while self.players[self.turn].left {
+            self.turn = (self.turn + 1) % self.players.len();
+        }
+        debug!("Pick {} as secret animal", self.secret_animal().name);
+        let mut events = vec![
+            GameEvent::RoundStarted { word_len: self.round.word_len() },
+            GameEvent::HintUpdated(self.round.hint()),
+        ];
+        if let Some(name) = self.current_player() {
+            events.push(GameEvent::TurnChanged(name.to_string()));
+        }
+        events
+    }
+
+    /// Consume one line of input from `player`: a command or a guess.
+    pub fn handle_input(&mut self, player: usize, input: &str) -> Vec<GameEvent> {
+        if self.over || !self.in_round || player >= self.players.len() || self.players[player].left {
+            return Vec::new()
+        }
+        let name = self.players[player].name.clone();
+        let catalog = &self.config.catalog;
+        match check_game_command(input) {
+            GameCommand::Quit if self.order == TurnOrder::Race => self.remove_player(player),
+            GameCommand::Quit => {
+                self.in_round = false;
+                self.over = true;
+                vec![GameEvent::Quit(catalog.format("player-quit", &[("player", &name)]))]
+            }
+            GameCommand::Scores => vec![GameEvent::ShowScores],
+            GameCommand::Hint => vec![GameEvent::GuessRejected(catalog.text("hint-solo-only").to_string())],
+            _ if self.order == TurnOrder::HotSeat && player != self.turn => {
+                vec![GameEvent::GuessRejected(catalog.format("not-your-turn", &[("player", &self.players[self.turn].name)]))]
+            }
+            _ if self.guesses_left(player) <= 0 => {
+                vec![GameEvent::GuessRejected(catalog.text("no-guesses-left").to_string())]
+            }
+            GameCommand::Next => {
+                // Passing gives up the rest of the round.
+                self.players[player].guess_count = self.config.mode.max_guess_count();
+                let mut events = vec![GameEvent::PlayerPassed(name)];
+                events.extend(self.next_turn());
+                events
+            }
+            GameCommand::PlayerInput(guess) => {
+                let mut events = vec![GameEvent::GuessSubmitted(guess.clone())];
+                events.extend(self.submit_guess(player, &guess));
+                events
+            }
+        }
+    }
+
+    /// Take `player` out of a race, when they quit or their connection is
+    /// lost. The game goes on until nobody is left.
+    pub fn remove_player(&mut self, player: usize) -> Vec<GameEvent> {
+        if self.over || player >= self.players.len() || self.players[player].left {
+            return Vec::new()
+        }
+        self.players[player].left = true;
+        let mut events = vec![GameEvent::PlayerLeft(self.players[player].name.clone())];
+        if self.players.iter().all(|player| player.left) {
+            self.in_round = false;
+            self.over = true;
+            events.push(GameEvent::GameOver);
+        } else if self.in_round && (self.order == TurnOrder::Race || player == self.turn) {
+            events.extend(self.next_turn());
+        }
+        events
+    }
+
+    fn submit_guess(&mut self, player: usize, guess: &str) -> Vec<GameEvent> {
+        let name = self.players[player].name.clone();
+        match check_animal_guess(guess, self.secret_animal(), &self.dictionary, &self.config.catalog) {
+            Guess::Right(right_msg) => {
+                self.in_round = false;
+                for (index, other) in self.players.iter_mut().enumerate() {
+                    other.win_streak = if index == player { other.win_streak + 1 } else { 0 };
+                }
+                let winner = &mut self.players[player];
+                let max_guesses = self.config.mode.max_guess_count();
+                let round_score = RoundScore::new(self.config.mode, winner.guess_count, max_guesses, None, winner.win_streak, 0);
+                winner.score += round_score.total();
+                let mut events = vec![
+                    GameEvent::RoundWon(format!("{}: {}", name, right_msg)),
+                    GameEvent::RoundScored(round_score),
+                    GameEvent::PlayerScored { player: name, score: winner.score },
+                ];
+                events.extend(self.finish_round());
+                events
+            }
+            Guess::Wrong { message: wrong_msg, feedback, .. } => {
+                let matched = self.round.record_wrong_guess(guess, &feedback, &format!("{}: {}", name, wrong_msg));
+                self.players[player].guess_count += 1;
+                let mut events = vec![
+                    GameEvent::GuessWrong { message: wrong_msg, matched, feedback },
+                    GameEvent::HintUpdated(self.round.hint()),
+                ];
+                events.extend(self.next_turn());
+                events
+            }
+            Guess::Invalid(invalid_msg) => vec![GameEvent::GuessRejected(invalid_msg)],
+        }
+    }
+
+    // Hand the turn to the next player with guesses left, or end the round
+    // when nobody has any.
+    fn next_turn(&mut self) -> Vec<GameEvent> {
+        let count = self.players.len();
+        let next = (1..=count)
+            .map(|offset| (self.turn + offset) % count)
+            .find(|&player| self.guesses_left(player) > 0);
+        match next {
+            None => {
+                self.in_round = false;
+                for player in &mut self.players {
+                    player.win_streak = 0;
+                }
+                let message = self.config.catalog.format("round-nobody-found", &[("animal", &self.secret_name())]);
+                let mut events = vec![GameEvent::RoundLost(message)];
+                events.extend(self.finish_round());
+                events
+            }
+            Some(next) if self.order == TurnOrder::HotSeat => {
+                self.turn = next;
+                vec![GameEvent::TurnChanged(self.players[next].name.clone())]
+            }
+            Some(_) => Vec::new(),
+        }
+    }
+
+    fn finish_round(&mut self) -> Vec<GameEvent> {
+        self.rounds_played += 1;
+        if self.rounds_played >= self.rounds {
+            self.over = true;
+            return vec![GameEvent::GameOver]
+        }
+        Vec::new()
+    }
+}
+
+impl GameView for MultiplayerGame {
+    fn header(&self, _player: &str) -> String {
+        let catalog = &self.config.catalog;
+        let mut header = catalog.format("header-round", &[
+            ("round", &self.round_number()),
+            ("rounds", &self.rounds),
+            ("mode", &self.config.mode.local_name(catalog)),
+            ("standings", &standings_text(&self.scores())),
+        ]);
+        if self.order == TurnOrder::HotSeat && self.in_round {
+            header.push_str(&catalog.format("header-turn", &[("player", &self.players[self.turn].name), ("guesses", &self.guesses_left(self.turn))]));
+        }
+        header
+    }
+
+    fn hint(&self) -> String {
+        self.round.hint()
+    }
+
+    fn word_len(&self) -> usize {
+        self.round.word_len()
+    }
+
+    fn visible_type(&self) -> Option<&str> {
+        MultiplayerGame::visible_type(self)
+    }
+
+    fn visible_features(&self) -> Option<&str> {
+        MultiplayerGame::visible_features(self)
+    }
+
+    fn history(&self) -> &[GuessRecord] {
+        self.round.history()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::MAX_GUESS_COUNT;
+    use crate::scoring::FIRST_TRY_BONUS;
+    use crate::mode::GameMode;
+
+    fn test_animals() -> Vec<Animal> {
+        vec![
+            Animal::new("Aardvark", "Mammalia", "Long, sticky tongue"),
+            Animal::new("Adelie Penguin", "Aves", "White eye ring"),
+        ]
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn game_needs_two_to_four_different_players() {
+        let new_game = |players: &[&str]| MultiplayerGame::new(test_animals(), GameConfig::default(), names(players), TurnOrder::HotSeat, 1);
+        assert!(new_game(&["Ann"]).is_err());
+        assert!(new_game(&["Ann", "Bob", "Cat", "Dan", "Eve"]).is_err());
+        assert!(new_game(&["Ann", "ann"]).is_err());
+        assert!(new_game(&["Ann", "Bob"]).is_ok());
+    }
+
+    #[test]
+    fn hot_seat_players_take_turns() {
+        let mut game = MultiplayerGame::new(test_animals(), GameConfig::default(), names(&["Ann", "Bob"]), TurnOrder::HotSeat, 2).unwrap();
+        let events = game.start_round_with(0);
+        assert_eq!(events.last(), Some(&GameEvent::TurnChanged("Ann".to_string())));
+        assert_eq!(game.handle_input(1, "aardwolf"), vec![GameEvent::GuessRejected("It's Ann's turn!".to_string())]);
+        let events = game.handle_input(0, "aardwolf");
+        assert_eq!(events.last(), Some(&GameEvent::TurnChanged("Bob".to_string())));
+        assert_eq!(game.history()[0].message, "Ann: You guessed wrong!");
+        let events = game.handle_input(1, "aardvark");
+        // Bob found it with the first guess of the round.
+        assert_eq!(events[3], GameEvent::PlayerScored { player: "Bob".to_string(), score: (MAX_GUESS_COUNT + FIRST_TRY_BONUS) * 2 });
+        assert_eq!(game.scores()[0].score, 0);
+        // Bob starts the second round.
+        game.start_round_with(1);
+        assert_eq!(game.current_player(), Some("Bob"));
+    }
+
+    #[test]
+    fn race_round_ends_when_everyone_is_out_of_guesses() {
+        let config = GameConfig { mode: GameMode::Hard, ..GameConfig::default() };
+        let mut game = MultiplayerGame::new(test_animals(), config, names(&["Ann", "Bob", "Cat"]), TurnOrder::Race, 1).unwrap();
+        game.start_round_with(0);
+        assert_eq!(game.current_player(), None);
+        game.handle_input(2, "next");
+        for _ in 0..GameMode::Hard.max_guess_count() {
+            game.handle_input(0, "aardwolf");
+        }
+        assert_eq!(game.handle_input(0, "aardwolf"), vec![GameEvent::GuessRejected("You have no guesses left this round.".to_string())]);
+        assert!(game.in_round());
+        for _ in 0..GameMode::Hard.max_guess_count() - 1 {
+            game.handle_input(1, "aardwolf");
+        }
+        let events = game.handle_input(1, "aardwolf");
+        assert!(matches!(events[3], GameEvent::RoundLost(_)));
+        assert_eq!(events.last(), Some(&GameEvent::GameOver));
+        assert_eq!(game.winners(), vec!["Ann", "Bob", "Cat"]);
+    }
+
+    #[test]
+    fn race_goes_on_until_everyone_left() {
+        let mut game = MultiplayerGame::new(test_animals(), GameConfig::default(), names(&["Ann", "Bob"]), TurnOrder::Race, 2).unwrap();
+        game.start_round_with(0);
+        assert_eq!(game.handle_input(0, "quit"), vec![GameEvent::PlayerLeft("Ann".to_string())]);
+        assert!(game.in_round() && !game.is_over());
+        assert!(game.handle_input(0, "aardvark").is_empty(), "Ann is out of the race");
+        assert_eq!(game.remove_player(1), vec![GameEvent::PlayerLeft("Bob".to_string()), GameEvent::GameOver]);
+        assert!(game.is_over());
+    }
+}
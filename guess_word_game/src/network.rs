@@ -0,0 +1,405 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+use crate::animal::Animal;
+use unicode_normalization::char::is_combining_mark;
+use crate::game::{GameEvent, GuessRecord};
+use crate::locale::Catalog;
+use crate::mode::{GameConfig, GameMode};
+use crate::multiplayer::{MultiplayerGame, PlayerScore, TurnOrder};
+use crate::view::{GameView, standings_text};
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// What a client sends to the host, one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Join { name: String },
+    Input(String),
+}
+
+/// What the host sends to its clients, one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// Everyone joined. `name` is the name the host gave this client.
+    Welcome { name: String, players: Vec<String>, mode: GameMode, rounds: u32 },
+    RoundStarted { round: u32, animal_type: Option<String>, features: Option<String> },
+    /// Events caused by the input of `player`, or by the host itself.
+    Events { player: Option<String>, events: Vec<GameEvent> },
+}
+
+fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+/// Read the next message, or None once the other side closed the connection.
+fn read_message<R: BufRead, T: DeserializeOwned>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None)
+    }
+    let message = serde_json::from_str(&line)?;
+    Ok(Some(message))
+}
+
+fn broadcast(writers: &mut [Option<TcpStream>], message: &ServerMessage) {
+    for writer in writers.iter_mut().flatten() {
+        if let Err(error) = write_message(writer, message) {
+            warn!("Failed to send to {:?}: {}", writer.peer_addr(), error);
+        }
+    }
+}
+
+// Pass the input of one client to the host loop. None tells it the client left.
+fn forward_input(player: usize, mut reader: BufReader<TcpStream>, sender: Sender<(usize, Option<String>)>) {
+    loop {
+        match read_message(&mut reader) {
+            Ok(Some(ClientMessage::Input(input))) => {
+                if sender.send((player, Some(input))).is_err() {
+                    return
+                }
+            }
+            Ok(Some(ClientMessage::Join { .. })) => {}
+            Err(ref error) if error.kind() == io::ErrorKind::InvalidData => warn!("Skipped a bad message of player {}: {}", player, error),
+            Ok(None) | Err(_) => {
+                let _ = sender.send((player, None));
+                return
+            }
+        }
+    }
+}
+
+// Give a player who joins with a name already taken a numbered one.
+fn unique_name(names: &[String], name: &str) -> String {
+    let name = if name.trim().is_empty() { "Player" } else { name.trim() };
+    let taken = |candidate: &str| names.iter().any(|known| known.to_lowercase() == candidate.to_lowercase());
+    let mut candidate = name.to_string();
+    let mut number = 2;
+    while taken(&candidate) {
+        candidate = format!("{} {}", name, number);
+        number += 1;
+    }
+    candidate
+}
+
+/// Host a race between `player_count` players joining on `listener`.
+/// The host keeps the only copy of the game: clients send their input and
+/// receive the events it caused. A player who leaves is out of the race,
+/// the game ends once nobody is left. Returns the final scores.
+pub fn host_game(listener: TcpListener, animals: Vec<Animal>, config: GameConfig, player_count: usize, rounds: u32) -> io::Result<Vec<PlayerScore>> {
+    let mut writers = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    let (sender, receiver) = mpsc::channel();
+    while names.len() < player_count {
+        let (stream, address) = listener.accept()?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let name = match read_message(&mut reader) {
+            Ok(Some(ClientMessage::Join { name })) => unique_name(&names, &name),
+            _ => {
+                warn!("{} did not send its name", address);
+                continue
+            }
+        };
+        info!("{} joined from {}", name, address);
+        let sender = sender.clone();
+        let player = names.len();
+        thread::spawn(move || forward_input(player, reader, sender));
+        names.push(name);
+        writers.push(Some(stream));
+    }
+
+    let mode = config.mode;
+    let mut game = MultiplayerGame::new(animals, config, names.clone(), TurnOrder::Race, rounds)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    info!("Session seed: {}", game.seed());
+    for (writer, name) in writers.iter_mut().flatten().zip(&names) {
+        write_message(writer, &ServerMessage::Welcome { name: name.clone(), players: names.clone(), mode, rounds })?;
+    }
+    while !game.is_over() {
+        if !game.in_round() {
+            let events = game.start_round();
+            if game.in_round() {
+                let round = ServerMessage::RoundStarted {
+                    round: game.round_number(),
+                    animal_type: game.visible_type().map(String::from),
+                    features: game.visible_features().map(String::from),
+                };
+                broadcast(&mut writers, &round);
+            }
+            broadcast(&mut writers, &ServerMessage::Events { player: None, events });
+            continue
+        }
+        let (player, input) = match receiver.recv() {
+            Ok(received) => received,
+            Err(_) => break,
+        };
+        let events = match input {
+            Some(input) => game.handle_input(player, &input),
+            None => {
+                info!("{} left", names[player]);
+                if let Some(writer) = writers[player].take() {
+                    let _ = writer.shutdown(Shutdown::Both);
+                }
+                game.remove_player(player)
+            }
+        };
+        broadcast(&mut writers, &ServerMessage::Events { player: Some(names[player].clone()), events });
+    }
+    for writer in writers.iter().flatten() {
+        let _ = writer.shutdown(Shutdown::Both);
+    }
+    Ok(game.scores())
+}
+
+/// Connection of one player to a host.
+pub struct Client {
+    writer: TcpStream,
+    messages: Receiver<ServerMessage>,
+}
+
+impl Client {
+    /// Connect and ask to join as `name`. The host answers with a
+    /// `Welcome` once every player is there.
+    pub fn join<A: ToSocketAddrs>(address: A, name: &str) -> io::Result<Client> {
+        let stream = TcpStream::connect(address)?;
+        let mut writer = stream.try_clone()?;
+        write_message(&mut writer, &ClientMessage::Join { name: name.to_string() })?;
+        let (sender, messages) = mpsc::channel();
+        let mut reader = BufReader::new(stream);
+        thread::spawn(move || {
+            while let Ok(Some(message)) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    return
+                }
+            }
+        });
+        Ok(Client { writer, messages })
+    }
+
+    pub fn send_input(&mut self, input: &str) -> io::Result<()> {
+        write_message(&mut self.writer, &ClientMessage::Input(input.to_string()))
+    }
+
+    /// Next message from the host, without waiting.
+    pub fn try_receive(&self) -> io::Result<Option<ServerMessage>> {
+        match self.messages.try_recv() {
+            Ok(message) => Ok(Some(message)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(disconnected()),
+        }
+    }
+
+    /// Wait up to `timeout` for the next message from the host.
+    pub fn receive_timeout(&self, timeout: Duration) -> io::Result<ServerMessage> {
+        match self.messages.recv_timeout(timeout) {
+            Ok(message) => Ok(message),
+            Err(RecvTimeoutError::Timeout) => Err(io::Error::new(io::ErrorKind::TimedOut, "the host did not answer")),
+            Err(RecvTimeoutError::Disconnected) => Err(disconnected()),
+        }
+    }
+}
+
+fn disconnected() -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionAborted, "the host closed the connection")
+}
+
+/// A client's copy of the game, rebuilt from the host messages so the
+/// game screen can draw it.
+#[derive(Debug, Default)]
+pub struct RemoteView {
+    name: String,
+    mode: Option<GameMode>,
+    scores: Vec<PlayerScore>,
+    round: u32,
+    rounds: u32,
+    hint: String,
+    animal_type: Option<String>,
+    features: Option<String>,
+    history: Vec<GuessRecord>,
+    over: bool,
+    catalog: Arc<Catalog>,
+}
+
+impl RemoteView {
+    /// View drawn with the messages of `catalog`.
+    pub fn new(catalog: Arc<Catalog>) -> RemoteView {
+        RemoteView { catalog, ..RemoteView::default() }
+    }
+
+    /// Name of this client's player, once the host welcomed it.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scores(&self) -> &[PlayerScore] {
+        &self.scores
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    pub fn apply(&mut self, message: &ServerMessage) {
+        match message {
+            ServerMessage::Welcome { name, players, mode, rounds } => {
+                self.name = name.clone();
+                self.scores = players.iter().map(|player| PlayerScore { name: player.clone(), score: 0 }).collect();
+                self.mode = Some(*mode);
+                self.rounds = *rounds;
+            }
+            ServerMessage::RoundStarted { round, animal_type, features } => {
+                self.round = *round;
+                self.animal_type = animal_type.clone();
+                self.features = features.clone();
+                self.history.clear();
+            }
+            ServerMessage::Events { player, events } => {
+                let mut guess = String::new();
+                for event in events {
+                    match event {
+                        GameEvent::GuessSubmitted(submitted) => guess = submitted.clone(),
+                        GameEvent::GuessWrong { message, feedback, .. } => {
+                            let message = format!("{}: {}", player.as_deref().unwrap_or(""), message);
+                            self.history.push(GuessRecord { guess: guess.clone(), feedback: feedback.clone(), message });
+                        }
+                        GameEvent::HintUpdated(hint) => self.hint = hint.clone(),
+                        GameEvent::PlayerScored { player, score } => {
+                            if let Some(entry) = self.scores.iter_mut().find(|entry| entry.name == *player) {
+                                entry.score = *score;
+                            }
+                        }
+                        GameEvent::GameOver | GameEvent::Quit(_) => self.over = true,
+                        GameEvent::PlayerLeft(left) if *left == self.name => self.over = true,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl GameView for RemoteView {
+    fn header(&self, player: &str) -> String {
+        let catalog = &self.catalog;
+        match self.mode {
+            Some(mode) => {
+                let round = catalog.format("header-round", &[
+                    ("round", &self.round),
+                    ("rounds", &self.rounds),
+                    ("mode", &mode.local_name(catalog)),
+                    ("standings", &standings_text(&self.scores)),
+                ]);
+                format!(" {} |{}", player, round)
+            }
+            None => catalog.format("header-waiting", &[("player", &player)]),
+        }
+    }
+
+    fn hint(&self) -> String {
+        self.hint.clone()
+    }
+
+    fn word_len(&self) -> usize {
+        self.hint.chars().filter(|&c| !c.is_whitespace() && !is_combining_mark(c)).count()
+    }
+
+    fn visible_type(&self) -> Option<&str> {
+        self.animal_type.as_deref()
+    }
+
+    fn visible_features(&self) -> Option<&str> {
+        self.features.as_deref()
+    }
+
+    fn history(&self) -> &[GuessRecord] {
+        &self.history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::FIRST_TRY_BONUS;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // Messages up to and including the first one carrying `event`.
+    fn receive_until(client: &Client, view: &mut RemoteView, event: &GameEvent) -> Vec<ServerMessage> {
+        let mut messages = Vec::new();
+        loop {
+            let message = client.receive_timeout(TIMEOUT).unwrap();
+            view.apply(&message);
+            messages.push(message.clone());
+            if let ServerMessage::Events { events, .. } = &message {
+                if events.contains(event) {
+                    return messages
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clients_race_on_the_host_game() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let animals = vec![Animal::new("Aardvark", "Mammalia", "Long, sticky tongue")];
+        let host = thread::spawn(move || host_game(listener, animals, GameConfig::default(), 2, 1));
+
+        let mut ann = Client::join(address, "Ann").unwrap();
+        let bob = Client::join(address, "Ann").unwrap();
+        let (mut ann_view, mut bob_view) = (RemoteView::default(), RemoteView::default());
+        receive_until(&ann, &mut ann_view, &GameEvent::HintUpdated("--------".to_string()));
+        receive_until(&bob, &mut bob_view, &GameEvent::HintUpdated("--------".to_string()));
+        assert_eq!(bob_view.name(), "Ann 2");
+        assert_eq!(bob_view.visible_features(), Some("Long, sticky tongue"));
+
+        ann.send_input("aardwolf").unwrap();
+        ann.send_input("aardvark").unwrap();
+        receive_until(&bob, &mut bob_view, &GameEvent::GameOver);
+        assert_eq!(bob_view.hint(), "Aard----");
+        assert_eq!(bob_view.history()[0].message, "Ann: You guessed wrong!");
+        assert_eq!(bob_view.scores()[0].score, 11 * 2);
+        assert!(bob_view.is_over());
+
+        let scores = host.join().unwrap().unwrap();
+        assert_eq!(scores, bob_view.scores().to_vec());
+    }
+
+    #[test]
+    fn the_race_goes_on_when_a_client_drops() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let animals = vec![Animal::new("Aardvark", "Mammalia", "Long, sticky tongue")];
+        let host = thread::spawn(move || host_game(listener, animals, GameConfig::default(), 3, 2));
+
+        let mut ann = Client::join(address, "Ann").unwrap();
+        let mut bob = Client::join(address, "Bob").unwrap();
+        let cat = Client::join(address, "Cat").unwrap();
+        let (mut ann_view, mut bob_view, mut cat_view) = (RemoteView::default(), RemoteView::default(), RemoteView::default());
+        let started = GameEvent::HintUpdated("--------".to_string());
+        receive_until(&ann, &mut ann_view, &started);
+        receive_until(&bob, &mut bob_view, &started);
+        receive_until(&cat, &mut cat_view, &started);
+
+        cat.writer.shutdown(Shutdown::Both).unwrap();
+        receive_until(&ann, &mut ann_view, &GameEvent::PlayerLeft("Cat".to_string()));
+        assert!(!ann_view.is_over());
+
+        ann.send_input("aardvark").unwrap();
+        receive_until(&bob, &mut bob_view, &started);
+        bob.send_input("aardwolf").unwrap();
+        bob.send_input("aardvark").unwrap();
+        receive_until(&ann, &mut ann_view, &GameEvent::GameOver);
+        let scores: Vec<i64> = ann_view.scores().iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, vec![(12 + FIRST_TRY_BONUS) * 2, 11 * 2, 0]);
+        assert_eq!(host.join().unwrap().unwrap(), ann_view.scores().to_vec());
+    }
+}
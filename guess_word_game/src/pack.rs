@@ -0,0 +1,287 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::animal::{Animal, VersionedAnimals, check_schema_version, generate_animal_data};
+use crate::matching::{is_valid_name_input, normalize_name};
+
+/// Why a word pack could not be loaded.
+#[derive(Debug)]
+pub enum PackError {
+    Io { path: PathBuf, error: io::Error },
+    Invalid { path: PathBuf, line: Option<usize>, message: String },
+    UnsupportedFormat(PathBuf),
+    Empty(PathBuf),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            PackError::Invalid { path, line: Some(line), message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            PackError::Invalid { path, line: None, message } => write!(f, "{}: {}", path.display(), message),
+            PackError::UnsupportedFormat(path) => write!(f, "{}: unsupported word pack format, expected .json, .csv or .toml", path.display()),
+            PackError::Empty(path) => write!(f, "{}: word pack has no animals", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackFormat {
+    Json,
+    Csv,
+    Toml,
+}
+
+#[derive(Deserialize)]
+struct TomlPack {
+    version: Option<u32>,
+    #[serde(default)]
+    animal: Vec<Animal>,
+}
+
+// (line, message) pair, turned into a PackError once the path is known.
+pub(crate) type ParseResult = Result<Vec<(usize, Animal)>, (Option<usize>, String)>;
+
+impl PackFormat {
+    pub fn from_path(path: &Path) -> Option<PackFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(PackFormat::Json),
+            "csv" => Some(PackFormat::Csv),
+            "toml" => Some(PackFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// Check that an animal entry can be played.
+pub fn validate_animal(animal: &Animal) -> Result<(), String> {
+    if animal.name.trim().is_empty() {
+        return Err("animal name is empty".to_string())
+    }
+    if animal.r#type.trim().is_empty() {
+        return Err(format!("animal '{}' has no type", animal.name))
+    }
+    for name in animal.names() {
+        if !is_valid_name_input(name) {
+            return Err(format!("animal name '{}' should contain only letters, spaces, hyphens and apostrophes", name))
+        }
+    }
+    Ok(())
+}
+
+// Line on which each element nested `depth` levels deep starts: 1 for
+// the entries of a bare array, 2 for the animals of a versioned file.
+fn json_entry_lines(text: &str, depth_of_entries: usize) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut line = 1;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                if depth == depth_of_entries {
+                    lines.push(line);
+                }
+                depth += 1;
+            }
+            '}' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    lines
+}
+
+fn parse_json(text: &str) -> ParseResult {
+    let json_error = |error: serde_json::Error| (Some(error.line()), error.to_string());
+    if !text.trim_start().starts_with('{') {
+        let animals: Vec<Animal> = serde_json::from_str(text).map_err(json_error)?;
+        return Ok(json_entry_lines(text, 1).into_iter().zip(animals).collect())
+    }
+    let versioned: VersionedAnimals = serde_json::from_str(text).map_err(json_error)?;
+    check_schema_version(versioned.version).map_err(|message| (None, message))?;
+    Ok(json_entry_lines(text, 2).into_iter().zip(versioned.animals).collect())
+}
+
+fn parse_csv(text: &str) -> ParseResult {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers = reader.headers()
+        .map_err(|error| (Some(1), error.to_string()))?
+        .clone();
+    let mut animals = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|error| (error.position().map(|pos| pos.line() as usize), error.to_string()))?;
+        let line = record.position().map(|pos| pos.line() as usize).unwrap_or(0);
+        let animal: Animal = record.deserialize(Some(&headers))
+            .map_err(|error| (Some(line), error.to_string()))?;
+        animals.push((line, animal));
+    }
+    Ok(animals)
+}
+
+fn parse_toml(text: &str) -> ParseResult {
+    let pack: TomlPack = toml::from_str(text)
+        .map_err(|error| (error.line_col().map(|(line, _)| line + 1), error.to_string()))?;
+    if let Some(version) = pack.version {
+        check_schema_version(version).map_err(|message| (None, message))?;
+    }
+    let lines = text.lines()
+        .enumerate()
+        .filter(|(_, line)| line.trim() == "[[animal]]")
+        .map(|(index, _)| index + 1);
+    Ok(lines.zip(pack.animal).collect())
+}
+
+/// Entries of a word pack with their line, not validated yet.
+pub(crate) fn parse_entries(text: &str, format: PackFormat) -> ParseResult {
+    match format {
+        PackFormat::Json => parse_json(text),
+        PackFormat::Csv => parse_csv(text),
+        PackFormat::Toml => parse_toml(text),
+    }
+}
+
+/// Parse and validate the text of a word pack.
+pub fn parse_pack(text: &str, format: PackFormat, path: &Path) -> Result<Vec<Animal>, PackError> {
+    let invalid = |(line, message)| PackError::Invalid { path: path.to_path_buf(), line, message };
+    let entries = parse_entries(text, format).map_err(invalid)?;
+    if entries.is_empty() {
+        return Err(PackError::Empty(path.to_path_buf()))
+    }
+    let mut animals = Vec::new();
+    for (line, animal) in entries {
+        validate_animal(&animal).map_err(|message| invalid((Some(line), message)))?;
+        animals.push(animal);
+    }
+    Ok(animals)
+}
+
+/// Read a word pack from disk, guessing its format from the file extension.
+pub fn load_pack(path: &Path) -> Result<Vec<Animal>, PackError> {
+    let format = PackFormat::from_path(path)
+        .ok_or_else(|| PackError::UnsupportedFormat(path.to_path_buf()))?;
+    let text = fs::read_to_string(path)
+        .map_err(|error| PackError::Io { path: path.to_path_buf(), error })?;
+    parse_pack(&text, format, path)
+}
+
+/// Merge several packs into one database. Animals with the same name
+/// (ignoring case and accents) are replaced by the entry from the later
+/// pack.
+pub fn merge_packs(packs: Vec<Vec<Animal>>) -> Vec<Animal> {
+    let mut merged: Vec<Animal> = Vec::new();
+    for animal in packs.into_iter().flatten() {
+        match merged.iter().position(|known| normalize_name(&known.name) == normalize_name(&animal.name)) {
+            Some(index) => {
+                debug!("Replace duplicated animal {}", animal.name);
+                merged[index] = animal;
+            }
+            None => merged.push(animal),
+        }
+    }
+    merged
+}
+
+/// Load and merge every pack in `paths`, in order.
+pub fn load_packs(paths: &[PathBuf]) -> Result<Vec<Animal>, PackError> {
+    let mut packs = Vec::new();
+    for path in paths {
+        packs.push(load_pack(path)?);
+    }
+    Ok(merge_packs(packs))
+}
+
+/// Animal database for a session: the given packs, or the compiled-in
+/// database when no pack is given.
+pub fn load_animal_data(paths: &[PathBuf]) -> Result<Vec<Animal>, PackError> {
+    if paths.is_empty() {
+        return Ok(generate_animal_data())
+    }
+    load_packs(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, format: PackFormat) -> Result<Vec<Animal>, PackError> {
+        parse_pack(text, format, Path::new("pack"))
+    }
+
+    #[test]
+    fn builtin_database_is_valid() {
+        let text = include_str!("animal_datas.json");
+        assert!(parse(text, PackFormat::Json).is_ok());
+    }
+
+    #[test]
+    fn all_formats_share_the_animal_schema() {
+        let json = r#"[{"name": "Kiwi", "type": "Aves", "features": "Tiny wings"}]"#;
+        let csv = "name,type,features\nKiwi,Aves,\"Tiny wings\"\n";
+        let toml = "[[animal]]\nname = \"Kiwi\"\ntype = \"Aves\"\nfeatures = \"Tiny wings\"\n";
+        for (text, format) in &[(json, PackFormat::Json), (csv, PackFormat::Csv), (toml, PackFormat::Toml)] {
+            let animals = parse(text, *format).unwrap();
+            assert_eq!(animals[0].name, "Kiwi");
+            assert_eq!(animals[0].features, "Tiny wings");
+        }
+    }
+
+    #[test]
+    fn invalid_entries_report_their_line() {
+        let json = "[\n  {\"name\": \"Kiwi\", \"type\": \"Aves\", \"features\": \"\"},\n  {\"name\": \"K1wi\", \"type\": \"Aves\", \"features\": \"\"}\n]";
+        let csv = "name,type,features\nKiwi,Aves,\nEmu,,Long legs\n";
+        let toml = "[[animal]]\nname = \"Kiwi\"\ntype = \"Aves\"\nfeatures = \"\"\n\n[[animal]]\nname = \"Emu\"\ntype = \"\"\nfeatures = \"Long legs\"\n";
+        let lines: Vec<Option<usize>> = vec![
+            parse(json, PackFormat::Json),
+            parse(csv, PackFormat::Csv),
+            parse(toml, PackFormat::Toml),
+        ].into_iter().map(|result| match result {
+            Err(PackError::Invalid { line, .. }) => line,
+            _ => None,
+        }).collect();
+        assert_eq!(lines, vec![Some(3), Some(3), Some(6)]);
+    }
+
+    #[test]
+    fn versioned_packs_carry_the_optional_fields() {
+        let json = "{\n  \"version\": 2,\n  \"animals\": [\n    {\"name\": \"Killer Whale\", \"type\": \"Mammalia\", \"features\": \"\", \"aliases\": [\"Orca\"], \"diet\": \"Carnivore\"},\n    {\"name\": \"Kiwi\", \"type\": \"\", \"features\": \"\"}\n  ]\n}";
+        assert!(matches!(parse(json, PackFormat::Json), Err(PackError::Invalid { line: Some(5), .. })));
+        let animals = parse(&json.replace("\"type\": \"\"", "\"type\": \"Aves\""), PackFormat::Json).unwrap();
+        assert_eq!(animals[0].aliases, vec!["Orca".to_string()]);
+        assert_eq!(animals[0].diet.as_deref(), Some("Carnivore"));
+        let csv = "name,type,features,aliases,continent\nKiller Whale,Mammalia,,Orca; Blackfish,\n";
+        let animals = parse(csv, PackFormat::Csv).unwrap();
+        assert_eq!(animals[0].aliases, vec!["Orca".to_string(), "Blackfish".to_string()]);
+        assert_eq!(animals[0].continent, None);
+        assert!(parse("{\"version\": 9, \"animals\": []}", PackFormat::Json).is_err());
+        assert!(parse("version = 9\n[[animal]]\nname = \"Kiwi\"\ntype = \"Aves\"\nfeatures = \"\"\n", PackFormat::Toml).is_err());
+    }
+
+    #[test]
+    fn later_packs_override_duplicated_names() {
+        let birds = parse("name,type,features\nKiwi,Aves,Tiny wings\n", PackFormat::Csv).unwrap();
+        let fixes = parse("name,type,features\nkiwi,Aves,Long beak\nEmu,Aves,Long legs\n", PackFormat::Csv).unwrap();
+        let merged = merge_packs(vec![birds, fixes]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].features, "Long beak");
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+use image::{ImageResult, RgbImage};
+use image::imageops::{self, FilterType};
+use crate::animal::Animal;
+
+/// Where `src/python/scrapeanimalimages.py` saves the pictures, as
+/// `<name>.jpg`.
+pub const IMAGE_DIR: &str = "src/image_resources";
+
+pub type Rgb = [u8; 3];
+
+/// Picture of the animal, if there is one on disk: the `image_path` of the
+/// database entry, or the scraped picture in IMAGE_DIR.
+pub fn find_image(animal: &Animal) -> Option<PathBuf> {
+    let path = match &animal.image_path {
+        Some(path) => PathBuf::from(path),
+        None => Path::new(IMAGE_DIR).join(format!("{}.jpg", animal.name)),
+    };
+    if path.is_file() {
+        return Some(path)
+    }
+    None
+}
+
+/// A picture made to be drawn with "▀" half blocks: each terminal cell
+/// shows two pixels, the top one as the foreground colour and the bottom
+/// one as the background colour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picture {
+    /// (top, bottom) pixel colours of each cell, row by row.
+    pub cells: Vec<Vec<(Rgb, Rgb)>>,
+}
+
+impl Picture {
+    pub const HALF_BLOCK: char = '▀';
+
+    /// Shrink `image` to fit `columns` x `rows` cells, keeping its shape.
+    pub fn from_image(image: &RgbImage, columns: u16, rows: u16) -> Picture {
+        let (width, height) = image.dimensions();
+        let scale = (f64::from(columns) / f64::from(width.max(1)))
+            .min(f64::from(rows) * 2.0 / f64::from(height.max(1)))
+            .min(1.0);
+        let pixel_width = ((f64::from(width) * scale) as u32).max(1);
+        let pixel_height = ((f64::from(height) * scale) as u32).max(1);
+        let small = imageops::resize(image, pixel_width, pixel_height, FilterType::Triangle);
+        let pixel = |x: u32, y: u32| -> Rgb {
+            if y < pixel_height { small.get_pixel(x, y).0 } else { [0, 0, 0] }
+        };
+        let cells = (0..pixel_height.div_ceil(2))
+            .map(|row| (0..pixel_width).map(|x| (pixel(x, row * 2), pixel(x, row * 2 + 1))).collect())
+            .collect();
+        Picture { cells }
+    }
+
+    pub fn load(path: &Path, columns: u16, rows: u16) -> ImageResult<Picture> {
+        let image = image::open(path)?.to_rgb8();
+        Ok(Picture::from_image(&image, columns, rows))
+    }
+
+    pub fn width(&self) -> u16 {
+        self.cells.first().map_or(0, |row| row.len() as u16)
+    }
+
+    pub fn height(&self) -> u16 {
+        self.cells.len() as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picture_fits_the_cells_and_keeps_its_shape() {
+        let image = RgbImage::from_fn(40, 20, |x, _| if x < 20 { image::Rgb([255, 0, 0]) } else { image::Rgb([0, 0, 255]) });
+        let picture = Picture::from_image(&image, 20, 20);
+        assert_eq!((picture.width(), picture.height()), (20, 5));
+        assert_eq!(picture.cells[0][0], ([255, 0, 0], [255, 0, 0]));
+        assert_eq!(picture.cells[4][19].0, [0, 0, 255]);
+    }
+
+    #[test]
+    fn missing_pictures_are_not_found() {
+        let mut animal = Animal::new("Aardvark", "Mammalia", "");
+        animal.image_path = Some("no/such/aardvark.jpg".to_string());
+        assert_eq!(find_image(&animal), None);
+    }
+}
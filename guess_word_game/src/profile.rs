@@ -0,0 +1,172 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::achievement::{Achievement, AchievementProgress, RoundRecord};
+use crate::mode::{GameKind, GameMode};
+
+pub const DEFAULT_SCORES_PATH: &str = "guess_word_game_scores.json";
+/// High scores kept per game and difficulty.
+pub const HIGH_SCORE_COUNT: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub name: String,
+    pub rounds_played: u64,
+    pub rounds_won: u64,
+    pub total_guesses: u64,
+    pub current_streak: u64,
+    pub longest_streak: u64,
+    #[serde(default)]
+    pub achievements: AchievementProgress,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub player: String,
+    /// Classic for scores saved before the table was split by game.
+    #[serde(default)]
+    pub kind: GameKind,
+    pub mode: GameMode,
+    pub score: i64,
+}
+
+/// Player profiles and high-score table, saved as a local JSON file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScoreBoard {
+    pub profiles: Vec<PlayerProfile>,
+    pub high_scores: Vec<HighScore>,
+}
+
+impl PlayerProfile {
+    pub fn average_guesses(&self) -> f64 {
+        if self.rounds_played == 0 {
+            return 0.0
+        }
+        self.total_guesses as f64 / self.rounds_played as f64
+    }
+}
+
+impl ScoreBoard {
+    /// Load the score board, starting a new one if the file does not exist yet.
+    pub fn load(path: &Path) -> io::Result<ScoreBoard> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(ScoreBoard::default()),
+            Err(error) => return Err(error),
+        };
+        serde_json::from_str(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, text)
+    }
+
+    pub fn profile(&self, player: &str) -> Option<&PlayerProfile> {
+        self.profiles.iter().find(|profile| profile.name == player)
+    }
+
+    pub fn profile_mut(&mut self, player: &str) -> &mut PlayerProfile {
+        match self.profiles.iter().position(|profile| profile.name == player) {
+            Some(index) => &mut self.profiles[index],
+            None => {
+                self.profiles.push(PlayerProfile { name: player.to_string(), ..PlayerProfile::default() });
+                self.profiles.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Record a finished round. `guesses` counts every valid guess of the round.
+    pub fn record_round(&mut self, player: &str, won: bool, guesses: u64) {
+        let profile = self.profile_mut(player);
+        profile.rounds_played += 1;
+        profile.total_guesses += guesses;
+        if won {
+            profile.rounds_won += 1;
+            profile.current_streak += 1;
+            profile.longest_streak = profile.longest_streak.max(profile.current_streak);
+        } else {
+            profile.current_streak = 0;
+        }
+    }
+
+    /// Count a finished round towards the achievements of a player.
+    /// Returns the ones it unlocked.
+    pub fn record_achievements(&mut self, player: &str, round: &RoundRecord) -> Vec<Achievement> {
+        self.profile_mut(player).achievements.record_round(round)
+    }
+
+    /// Add a final score, keeping only the best HIGH_SCORE_COUNT per game
+    /// and mode. Returns the rank of the new score if it made the table.
+    pub fn add_high_score(&mut self, player: &str, kind: GameKind, mode: GameMode, score: i64) -> Option<usize> {
+        let rank = self.top_scores(kind, mode).iter().filter(|high_score| high_score.score >= score).count();
+        if rank >= HIGH_SCORE_COUNT {
+            return None
+        }
+        self.high_scores.push(HighScore { player: player.to_string(), kind, mode, score });
+        self.high_scores.sort_by_key(|high_score| Reverse(high_score.score));
+        let mut kept = 0;
+        self.high_scores.retain(|high_score| {
+            if (high_score.kind, high_score.mode) != (kind, mode) {
+                return true
+            }
+            kept += 1;
+            kept <= HIGH_SCORE_COUNT
+        });
+        Some(rank + 1)
+    }
+
+    /// High scores of a game in a mode, best first.
+    pub fn top_scores(&self, kind: GameKind, mode: GameMode) -> Vec<&HighScore> {
+        let mut scores: Vec<&HighScore> = self.high_scores.iter().filter(|high_score| high_score.kind == kind && high_score.mode == mode).collect();
+        scores.sort_by_key(|high_score| Reverse(high_score.score));
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_update_streaks_and_averages() {
+        let mut board = ScoreBoard::default();
+        board.record_round("kirk", true, 1);
+        board.record_round("kirk", true, 3);
+        board.record_round("kirk", false, 5);
+        board.record_round("kirk", true, 3);
+        let profile = board.profile("kirk").unwrap();
+        assert_eq!(profile.rounds_played, 4);
+        assert_eq!(profile.longest_streak, 2);
+        assert_eq!(profile.current_streak, 1);
+        assert_eq!(profile.average_guesses(), 3.0);
+    }
+
+    #[test]
+    fn high_score_table_is_capped_per_mode() {
+        let mut board = ScoreBoard::default();
+        for score in 0..HIGH_SCORE_COUNT as i64 {
+            board.add_high_score("kirk", GameKind::Classic, GameMode::Easy, score + 10);
+        }
+        assert_eq!(board.add_high_score("kirk", GameKind::Classic, GameMode::Easy, 5), None);
+        assert_eq!(board.add_high_score("kirk", GameKind::Classic, GameMode::Easy, 15), Some(6));
+        assert_eq!(board.add_high_score("kirk", GameKind::Classic, GameMode::Hard, 1), Some(1));
+        assert_eq!(board.top_scores(GameKind::Classic, GameMode::Easy).len(), HIGH_SCORE_COUNT);
+        assert_eq!(board.top_scores(GameKind::Classic, GameMode::Easy)[0].score, 19);
+    }
+
+    #[test]
+    fn each_game_has_its_own_high_scores() {
+        let mut board = ScoreBoard::default();
+        board.add_high_score("kirk", GameKind::Classic, GameMode::Easy, 50);
+        assert_eq!(board.add_high_score("kirk", GameKind::Hangman, GameMode::Easy, 5), Some(1));
+        assert_eq!(board.add_high_score("kirk", GameKind::Quiz, GameMode::Easy, 80), Some(1));
+        assert_eq!(board.top_scores(GameKind::Classic, GameMode::Easy).len(), 1);
+        assert_eq!(board.top_scores(GameKind::Hangman, GameMode::Easy)[0].score, 5);
+
+        let old: ScoreBoard = serde_json::from_str(r#"{"profiles": [], "high_scores": [{"player": "kirk", "mode": "Hard", "score": 9}]}"#).unwrap();
+        assert_eq!(old.top_scores(GameKind::Classic, GameMode::Hard)[0].score, 9, "older tables were classic games");
+    }
+}
@@ -0,0 +1,142 @@
+use rand::Rng;
+use unicode_normalization::UnicodeNormalization;
+use crate::animal::Animal;
+use crate::builder::normalize_class;
+use crate::filter::is_class_name;
+use crate::game::Guess;
+use crate::locale::Catalog;
+use crate::matching::{fold_letter, is_valid_name_input, normalize_name};
+
+// Shuffles tried before an anagram is allowed to spell the name itself.
+const SCRAMBLE_TRIES: usize = 8;
+
+/// Anagram: the letters of each word of `name` shuffled, in capitals. A
+/// name with any two different letters never comes back unscrambled.
+pub fn scramble<R: Rng>(name: &str, rng: &mut R) -> String {
+    let name: String = name.nfc().flat_map(char::to_uppercase).collect();
+    let mut words: Vec<Vec<char>> = name.split_whitespace().map(|word| word.chars().collect()).collect();
+    for _ in 0..SCRAMBLE_TRIES {
+        for word in &mut words {
+            rng.shuffle(word);
+        }
+        let scrambled: Vec<String> = words.iter().map(|word| word.iter().collect()).collect();
+        let scrambled = scrambled.join(" ");
+        if scrambled != name {
+            return scrambled
+        }
+    }
+    name
+}
+
+/// Word chain: the letter the next animal must start with, the last
+/// letter of `name` without its accents.
+pub fn chain_letter(name: &str) -> Option<char> {
+    name.chars().rev().find(|c| c.is_alphabetic()).map(fold_letter)
+}
+
+fn first_letter(name: &str) -> Option<char> {
+    name.chars().find(|c| c.is_alphabetic()).map(fold_letter)
+}
+
+/// Word chain: animals whose name in `locale` may follow the animal at
+/// `after`, leaving out those already in the chain.
+pub fn chain_answers(animals: &[Animal], locale: &str, after: usize, chain: &[usize]) -> Vec<usize> {
+    let letter = chain_letter(animals[after].local_name(locale));
+    (0..animals.len())
+        .filter(|index| *index != after && !chain.contains(index))
+        .filter(|&index| letter.is_some() && first_letter(animals[index].local_name(locale)) == letter)
+        .collect()
+}
+
+/// The animal of the pack called `name`, by any of its names.
+pub fn find_animal(animals: &[Animal], name: &str) -> Option<usize> {
+    let name = normalize_name(name);
+    animals.iter().position(|animal| animal.names().any(|known| normalize_name(known) == name))
+}
+
+/// Classes of the pack, sorted and without duplicates.
+pub fn animal_classes(animals: &[Animal]) -> Vec<String> {
+    let mut classes: Vec<String> = animals.iter().map(|animal| animal.r#type.clone()).collect();
+    classes.sort_by_key(|class| class.to_lowercase());
+    classes.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    classes
+}
+
+/// Class quiz: compare a guess with the class of `animal`. Everyday names
+/// like "birds" or "fish" are accepted for the classes they stand for; a guess
+/// that is none of `classes` is rejected without costing a guess.
+pub fn check_class_guess(guess: &str, animal: &Animal, classes: &[String], catalog: &Catalog) -> Guess {
+    if !is_valid_name_input(guess) {
+        return Guess::Invalid(catalog.text("guess-invalid").to_string())
+    }
+    let name = animal.local_name(catalog.locale());
+    if is_class_name(guess, &animal.r#type) {
+        return Guess::Right(catalog.format("quiz-right", &[("animal", &name), ("class", &animal.r#type)]))
+    }
+    if !classes.iter().any(|class| is_class_name(guess, class)) {
+        return Guess::Invalid(catalog.format("quiz-unknown-class", &[("classes", &classes.join(", "))]))
+    }
+    Guess::Wrong { message: catalog.format("quiz-wrong", &[("class", &normalize_class(guess))]), feedback: Vec::new(), close: false }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::seeded_rng;
+    use crate::filter::AnimalFilter;
+
+    fn test_animals() -> Vec<Animal> {
+        vec![
+            Animal::new("Aardvark", "Mammalia", "Long, sticky tongue"),
+            Animal::new("Kiwi", "Aves", "Nostrils at the tip of its beak"),
+            Animal::new("Koala", "Mammalia", "Eats eucalyptus"),
+            Animal::new("Iguana", "Reptilia", "Dewlap"),
+        ]
+    }
+
+    #[test]
+    fn anagrams_keep_the_letters_of_each_word() {
+        let mut rng = seeded_rng(3);
+        let scrambled = scramble("Adelie Penguin", &mut rng);
+        assert_ne!(scrambled, "ADELIE PENGUIN");
+        let mut letters: Vec<Vec<char>> = scrambled.split(' ').map(|word| word.chars().collect()).collect();
+        letters.iter_mut().for_each(|word| word.sort());
+        assert_eq!(letters, vec!["ADEEIL".chars().collect::<Vec<_>>(), "EGINNPU".chars().collect()]);
+        assert_eq!(scramble("Oo", &mut rng), "OO");
+    }
+
+    #[test]
+    fn chains_follow_the_last_letter() {
+        let animals = test_animals();
+        assert_eq!(chain_letter("Aardvark"), Some('k'));
+        assert_eq!(chain_answers(&animals, "en", 0, &[0]), vec![1, 2]);
+        assert_eq!(chain_answers(&animals, "en", 1, &[0, 1]), vec![3]);
+        assert!(chain_answers(&animals, "en", 3, &[0, 1, 3]).is_empty(), "the aardvark is already in the chain");
+        assert_eq!(find_animal(&animals, "KOALA"), Some(2));
+    }
+
+    #[test]
+    fn class_answers_are_checked_against_the_pack() {
+        let animals = test_animals();
+        let classes = animal_classes(&animals);
+        assert_eq!(classes, vec!["Aves", "Mammalia", "Reptilia"]);
+        let catalog = Catalog::english();
+        assert!(matches!(check_class_guess("mammals", &animals[0], &classes, &catalog), Guess::Right(_)));
+        assert!(matches!(check_class_guess("aves", &animals[0], &classes, &catalog), Guess::Wrong { .. }));
+        assert!(matches!(check_class_guess("Insecta", &animals[0], &classes, &catalog), Guess::Invalid(_)));
+    }
+
+    #[test]
+    fn class_answers_take_the_same_nicknames_as_filters() {
+        let mut animals = test_animals();
+        animals.push(Animal::new("Clownfish", "Actinopterygii", "Lives in sea anemones"));
+        let classes = animal_classes(&animals);
+        let catalog = Catalog::english();
+        assert!(matches!(check_class_guess("fish", &animals[4], &classes, &catalog), Guess::Right(_)));
+        assert!(matches!(check_class_guess("Fishes", &animals[4], &classes, &catalog), Guess::Right(_)));
+        assert!(matches!(check_class_guess("fish", &animals[1], &classes, &catalog), Guess::Wrong { .. }));
+        assert!(matches!(check_class_guess("fish", &animals[1], &classes[1..], &catalog), Guess::Invalid(_)), "no fish in the pack");
+        assert_eq!(normalize_class("fish"), "Actinopterygii");
+        assert!(AnimalFilter::parse("class=fish").unwrap().matches(&animals[4]));
+    }
+}
@@ -0,0 +1,391 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::animal::Animal;
+use crate::clock::{Clock, ManualClock};
+use crate::game::{GameEvent, GameSession};
+use crate::locale::Catalog;
+use crate::mode::{GameConfig, GameKind, GameMode};
+
+/// Version of the session log format, bumped when old logs stop replaying.
+pub const LOG_VERSION: u32 = 1;
+
+/// What the front end asked of the session at one step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LogAction {
+    StartRound,
+    /// A timer ran out while waiting for input.
+    Tick,
+    Input(String),
+}
+
+/// How a step ended a round or the session.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    Won,
+    Lost,
+    GameOver,
+    Quit,
+}
+
+/// The state a step left the session in, compared on replay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepResult {
+    /// Secret animal of a started round.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animal: Option<String>,
+    pub score: i64,
+    /// Hints shown, as the player read them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outcomes: Vec<Outcome>,
+}
+
+/// One step of a session: when it happened on the session clock, what was
+/// asked and what came of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogStep {
+    pub at: Duration,
+    pub action: LogAction,
+    #[serde(flatten)]
+    pub result: StepResult,
+}
+
+/// Everything needed to play a solo session again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLog {
+    pub version: u32,
+    pub seed: u64,
+    #[serde(default)]
+    pub kind: GameKind,
+    pub mode: GameMode,
+    #[serde(default)]
+    pub round_time: Option<Duration>,
+    #[serde(default)]
+    pub blitz_time: Option<Duration>,
+    pub locale: String,
+    /// `Catalog::version` of the messages the session was played with.
+    #[serde(default)]
+    pub catalog_version: Option<String>,
+    /// The animals the session drew from, after filters.
+    pub animals: Vec<Animal>,
+    /// Animals that had a picture, so the picture hint comes up on replay
+    /// as it did in the game. Older logs look on disk.
+    #[serde(default)]
+    pub pictures: Option<Vec<String>>,
+    pub steps: Vec<LogStep>,
+}
+
+/// Where a replay stopped agreeing with its log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Index of the step in the log.
+    pub step: usize,
+    pub action: LogAction,
+    pub expected: StepResult,
+    pub actual: StepResult,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayReport {
+    pub steps: usize,
+    pub expected_score: i64,
+    pub final_score: i64,
+    pub divergence: Option<Divergence>,
+}
+
+impl fmt::Display for LogAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogAction::StartRound => write!(f, "round start"),
+            LogAction::Tick => write!(f, "timer"),
+            LogAction::Input(input) => write!(f, "input '{}'", input),
+        }
+    }
+}
+
+impl fmt::Display for StepResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(animal) = &self.animal {
+            write!(f, "{}, ", animal)?;
+        }
+        write!(f, "score {}", self.score)?;
+        for hint in &self.hints {
+            write!(f, ", hint '{}'", hint)?;
+        }
+        for outcome in &self.outcomes {
+            write!(f, ", {:?}", outcome)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.divergence {
+            Some(divergence) => write!(f, "Diverged at step {} ({}): expected {}, got {}",
+                divergence.step + 1, divergence.action, divergence.expected, divergence.actual),
+            None => write!(f, "Replayed {} steps, final score {} as recorded", self.steps, self.final_score),
+        }
+    }
+}
+
+impl StepResult {
+    fn of(action: &LogAction, session: &GameSession, events: &[GameEvent]) -> StepResult {
+        let started = events.iter().any(|event| matches!(event, GameEvent::RoundStarted { .. }));
+        StepResult {
+            animal: if *action == LogAction::StartRound && started { Some(session.secret_animal().name.clone()) } else { None },
+            score: session.score(),
+            hints: events.iter().filter_map(|event| match event {
+                GameEvent::HintGiven { hint, .. } => Some(hint.clone()),
+                _ => None,
+            }).collect(),
+            outcomes: events.iter().filter_map(|event| match event {
+                GameEvent::RoundWon(_) => Some(Outcome::Won),
+                GameEvent::RoundLost(_) => Some(Outcome::Lost),
+                GameEvent::GameOver => Some(Outcome::GameOver),
+                GameEvent::Quit(_) => Some(Outcome::Quit),
+                _ => None,
+            }).collect(),
+        }
+    }
+}
+
+impl SessionLog {
+    /// An empty log of `session`, before its first round.
+    pub fn new(session: &GameSession) -> SessionLog {
+        let config = session.config();
+        SessionLog {
+            version: LOG_VERSION,
+            seed: session.seed(),
+            kind: config.kind,
+            mode: config.mode,
+            round_time: config.round_time,
+            blitz_time: config.blitz_time,
+            locale: config.catalog.locale().to_string(),
+            catalog_version: Some(config.catalog.version()),
+            animals: session.animals().to_vec(),
+            pictures: Some(session.animals_with_pictures()),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<SessionLog> {
+        let text = fs::read_to_string(path)?;
+        let log: SessionLog = serde_json::from_str(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        if log.version > LOG_VERSION {
+            let message = format!("log version {} is newer than this game understands ({})", log.version, LOG_VERSION);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message))
+        }
+        Ok(log)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, text)
+    }
+
+    /// Add what `action` did to the session. Timer checks are only worth
+    /// logging when they return events.
+    pub fn record(&mut self, at: Duration, action: LogAction, session: &GameSession, events: &[GameEvent]) {
+        if action == LogAction::Tick && events.is_empty() {
+            return
+        }
+        let result = StepResult::of(&action, session, events);
+        self.steps.push(LogStep { at, action, result });
+    }
+
+    /// Score the session had after its last step.
+    pub fn final_score(&self) -> Option<i64> {
+        self.steps.last().map(|step| step.result.score)
+    }
+
+    /// Play the log again with the built-in messages of its language.
+    pub fn replay(&self) -> Result<ReplayReport, String> {
+        self.replay_with(Catalog::for_locale(&self.locale))
+    }
+
+    /// Play the log again on a clock moved to the recorded times, stopping
+    /// at the first step whose round, score, hints or outcome differs.
+    /// Refused if `catalog` is not the one the session was played with.
+    pub fn replay_with(&self, catalog: Catalog) -> Result<ReplayReport, String> {
+        if catalog.locale() != self.locale {
+            return Err(format!("the log was played in '{}', not '{}'", self.locale, catalog.locale()))
+        }
+        if let Some(version) = self.catalog_version.as_ref().filter(|version| **version != catalog.version()) {
+            return Err(format!("the log was played with other '{}' messages (catalog version {}, this one is {})", self.locale, version, catalog.version()))
+        }
+        let clock = Rc::new(ManualClock::new());
+        let config = GameConfig {
+            mode: self.mode,
+            round_time: self.round_time,
+            blitz_time: self.blitz_time,
+            seed: Some(self.seed),
+            catalog: Arc::new(catalog),
+            kind: self.kind,
+        };
+        let mut session = GameSession::with_clock(self.animals.clone(), config, clock.clone());
+        if let Some(pictures) = &self.pictures {
+            session.set_pictures(pictures.clone());
+        }
+        let mut report = ReplayReport {
+            steps: 0,
+            expected_score: self.final_score().unwrap_or_else(|| session.score()),
+            final_score: session.score(),
+            divergence: None,
+        };
+        for (index, step) in self.steps.iter().enumerate() {
+            clock.advance(step.at.checked_sub(clock.now()).unwrap_or_default());
+            let events = match &step.action {
+                LogAction::StartRound => session.start_round(),
+                LogAction::Tick => session.tick(),
+                LogAction::Input(input) => session.handle_input(input),
+            };
+            let actual = StepResult::of(&step.action, &session, &events);
+            report.steps += 1;
+            report.final_score = session.score();
+            if actual != step.result {
+                report.divergence = Some(Divergence { step: index, action: step.action.clone(), expected: step.result.clone(), actual });
+                break
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// A session log written to a file after every finished round, so a
+/// crash keeps all the rounds before it.
+pub struct SessionRecorder {
+    log: SessionLog,
+    path: PathBuf,
+}
+
+impl SessionRecorder {
+    pub fn new(session: &GameSession, path: &Path) -> SessionRecorder {
+        SessionRecorder { log: SessionLog::new(session), path: path.to_path_buf() }
+    }
+
+    pub fn log(&self) -> &SessionLog {
+        &self.log
+    }
+
+    pub fn record(&mut self, at: Duration, action: LogAction, session: &GameSession, events: &[GameEvent]) -> io::Result<()> {
+        self.log.record(at, action, session, events);
+        if self.log.steps.last().is_some_and(|step| !step.result.outcomes.is_empty()) {
+            return self.save()
+        }
+        Ok(())
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.log.save(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_animals() -> Vec<Animal> {
+        vec![
+            Animal::new("Aardvark", "Mammalia", "Long, sticky tongue"),
+            Animal::new("Adelie Penguin", "Aves", "White eye ring"),
+            Animal::new("African Bush Elephant", "Mammalia", "Huge ears"),
+        ]
+    }
+
+    // Play a few rounds on a manual clock, logging every step.
+    fn recorded_session() -> SessionLog {
+        let clock = Rc::new(ManualClock::new());
+        let config = GameConfig { round_time: Some(Duration::from_secs(30)), seed: Some(7), ..GameConfig::default() };
+        let mut session = GameSession::with_clock(test_animals(), config, clock.clone());
+        let mut log = SessionLog::new(&session);
+        let play = |log: &mut SessionLog, session: &mut GameSession, action: LogAction| {
+            clock.advance(Duration::from_millis(2300));
+            let events = match &action {
+                LogAction::StartRound => session.start_round(),
+                LogAction::Tick => session.tick(),
+                LogAction::Input(input) => session.handle_input(input),
+            };
+            log.record(clock.now(), action, session, &events);
+        };
+        play(&mut log, &mut session, LogAction::StartRound);
+        let secret = session.secret_animal().name.clone();
+        play(&mut log, &mut session, LogAction::Input("aardwolf".to_string()));
+        play(&mut log, &mut session, LogAction::Input("hint".to_string()));
+        play(&mut log, &mut session, LogAction::Input(secret));
+        play(&mut log, &mut session, LogAction::StartRound);
+        play(&mut log, &mut session, LogAction::Tick);
+        play(&mut log, &mut session, LogAction::Input("next".to_string()));
+        log
+    }
+
+    #[test]
+    fn replay_follows_the_log() {
+        let log = recorded_session();
+        assert_eq!(log.steps.len(), 6, "the empty timer check is not logged");
+        let text = serde_json::to_string(&log).unwrap();
+        let log: SessionLog = serde_json::from_str(&text).unwrap();
+        let report = log.replay().unwrap();
+        assert_eq!(report.divergence, None);
+        assert_eq!(report.steps, 6);
+        assert_eq!(Some(report.final_score), log.final_score());
+    }
+
+    #[test]
+    fn replay_reports_the_first_divergence() {
+        let mut log = recorded_session();
+        log.steps[3].action = LogAction::Input("next".to_string());
+        let report = log.replay().unwrap();
+        let divergence = report.divergence.unwrap();
+        assert_eq!(divergence.step, 3);
+        assert_eq!(divergence.expected.outcomes, vec![Outcome::Won]);
+        assert_eq!(divergence.actual.outcomes, vec![Outcome::Lost]);
+
+        let mut log = recorded_session();
+        for animal in &mut log.animals {
+            animal.name = animal.name.to_uppercase();
+        }
+        assert_eq!(log.replay().unwrap().divergence.map(|divergence| divergence.step), Some(0));
+    }
+
+    #[test]
+    fn replay_offers_the_pictures_of_the_log() {
+        let animals = vec![Animal::new("Aardvark", "Mammalia", "Long, sticky tongue")];
+        let mut session = GameSession::with_clock(animals, GameConfig { seed: Some(3), ..GameConfig::default() }, Rc::new(ManualClock::new()));
+        session.set_pictures(vec!["Aardvark".to_string()]);
+        let mut log = SessionLog::new(&session);
+        assert_eq!(log.pictures, Some(vec!["Aardvark".to_string()]));
+        let events = session.start_round();
+        log.record(Duration::from_secs(1), LogAction::StartRound, &session, &events);
+        while session.next_hint().is_some() {
+            let events = session.handle_input("hint");
+            log.record(Duration::from_secs(2), LogAction::Input("hint".to_string()), &session, &events);
+        }
+        let picture_hint = Catalog::english().text("hint-picture").to_string();
+        assert_eq!(log.steps.last().unwrap().result.hints, vec![picture_hint]);
+        assert_eq!(log.replay().unwrap().divergence, None, "no picture on disk, but the log had one");
+
+        log.pictures = Some(Vec::new());
+        let divergence = log.replay().unwrap().divergence.unwrap();
+        assert_eq!(divergence.step, log.steps.len() - 1);
+        assert!(divergence.actual.hints.is_empty());
+    }
+
+    #[test]
+    fn replay_refuses_other_messages() {
+        let mut log = recorded_session();
+        assert_eq!(log.catalog_version, Some(Catalog::english().version()));
+        assert!(log.replay_with(Catalog::for_locale("fr")).is_err());
+        log.catalog_version = Some("0".to_string());
+        assert!(log.replay().is_err());
+        log.catalog_version = None;
+        assert!(log.replay().is_ok(), "older logs have no catalog version");
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeSet;
+use crate::animal::Animal;
+use crate::builder::normalize_class;
+use crate::hint::HintTier;
+use crate::locale::Catalog;
+use crate::matching::normalize_name;
+
+/// Questions the computer may ask before it has to guess.
+pub const MAX_QUESTIONS: usize = 20;
+/// Guesses it makes once the questions are used up or stop helping.
+pub const MAX_REVERSE_GUESSES: usize = 3;
+pub const DEFAULT_LEARNED_PACK: &str = "learned_animals.json";
+
+// Shortest word worth asking about.
+const MIN_WORD_LEN: usize = 4;
+// Words of the feature descriptions that say nothing about an animal.
+const STOP_WORDS: [&str; 12] = ["with", "their", "they", "them", "from", "that", "have", "this", "into", "than", "each", "very"];
+
+/// A yes or no question about the animal the player thinks of.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Question {
+    Class(String),
+    /// One of the optional facts, like the habitat, by its whole value.
+    Fact(HintTier, String),
+    /// A word that several animal names share, like "penguin".
+    NameWord(String),
+    /// A word of the feature description.
+    FeatureWord(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Answer {
+    Yes,
+    No,
+    DontKnow,
+}
+
+/// What the computer does next.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReverseStep {
+    Ask(Question),
+    /// Guess the animal at this index of the pack.
+    Guess(usize),
+    GiveUp,
+}
+
+/// A 20-questions game where the computer finds the animal the player
+/// thinks of. Questions are picked to split the animals still possible
+/// as evenly as possible, which gains the most information per answer.
+pub struct ReverseGame {
+    animals: Vec<Animal>,
+    candidates: Vec<usize>,
+    answers: Vec<(Question, Answer)>,
+    guesses: Vec<usize>,
+}
+
+// Lowercase words of `text` worth a question.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphabetic())
+        .map(str::to_lowercase)
+        .filter(|word| word.chars().count() >= MIN_WORD_LEN && !STOP_WORDS.contains(&word.as_str()))
+}
+
+/// Entropy of a yes/no split of `total` equally likely animals, in bits.
+pub fn split_entropy(yes: usize, total: usize) -> f64 {
+    if yes == 0 || yes >= total {
+        return 0.0
+    }
+    let p = yes as f64 / total as f64;
+    -(p * p.log2() + (1.0 - p) * (1.0 - p).log2())
+}
+
+impl Question {
+    pub fn applies(&self, animal: &Animal) -> bool {
+        match self {
+            Question::Class(class) => animal.r#type.eq_ignore_ascii_case(class),
+            Question::Fact(tier, value) => tier.fact(animal).is_some_and(|fact| fact.eq_ignore_ascii_case(value)),
+            Question::NameWord(word) => animal.names().any(|name| words(name).any(|name_word| name_word == *word)),
+            Question::FeatureWord(word) => words(&animal.features).any(|feature_word| feature_word == *word),
+        }
+    }
+
+    pub fn text(&self, catalog: &Catalog) -> String {
+        match self {
+            Question::Class(class) => catalog.format("question-class", &[("class", class)]),
+            Question::Fact(tier, value) => catalog.format("question-fact", &[("fact", &catalog.text(tier.message_key())), ("value", value)]),
+            Question::NameWord(word) => catalog.format("question-name", &[("word", word)]),
+            Question::FeatureWord(word) => catalog.format("question-feature", &[("word", word)]),
+        }
+    }
+}
+
+impl Answer {
+    /// Read an answer in the catalog language or in English: `yes`, `no`
+    /// or `?`.
+    pub fn parse(input: &str, catalog: &Catalog) -> Option<Answer> {
+        let input = input.trim().to_lowercase();
+        let listed = |key: &str, english: &[&str]| {
+            catalog.text(key).split(',').map(str::trim).chain(english.iter().cloned()).any(|word| word == input)
+        };
+        if input == "?" {
+            Some(Answer::DontKnow)
+        } else if listed("answer-yes-words", &["yes", "y"]) {
+            Some(Answer::Yes)
+        } else if listed("answer-no-words", &["no", "n"]) {
+            Some(Answer::No)
+        } else {
+            None
+        }
+    }
+}
+
+impl ReverseGame {
+    pub fn new(animals: Vec<Animal>) -> ReverseGame {
+        ReverseGame { candidates: (0..animals.len()).collect(), animals, answers: Vec::new(), guesses: Vec::new() }
+    }
+
+    pub fn animals(&self) -> &[Animal] {
+        &self.animals
+    }
+
+    /// Animals that fit every answer so far and weren't guessed yet.
+    pub fn candidates(&self) -> impl Iterator<Item = &Animal> {
+        self.candidates.iter().map(move |index| &self.animals[*index])
+    }
+
+    pub fn answers(&self) -> &[(Question, Answer)] {
+        &self.answers
+    }
+
+    // Every question the pack could answer, in a stable order.
+    fn questions(&self) -> BTreeSet<Question> {
+        let mut questions = BTreeSet::new();
+        let mut name_words = BTreeSet::new();
+        for index in &self.candidates {
+            let animal = &self.animals[*index];
+            questions.insert(Question::Class(animal.r#type.clone()));
+            for tier in HintTier::ALL.iter().filter(|tier| tier.is_fact()) {
+                if let Some(fact) = tier.fact(animal) {
+                    questions.insert(Question::Fact(*tier, fact.to_string()));
+                }
+            }
+            for word in animal.names().flat_map(words) {
+                if !name_words.insert(word.clone()) {
+                    questions.insert(Question::NameWord(word));
+                }
+            }
+            questions.extend(words(&animal.features).map(Question::FeatureWord));
+        }
+        questions
+    }
+
+    /// The question that gains the most information about the remaining
+    /// animals, if any of them splits them at all.
+    pub fn best_question(&self) -> Option<Question> {
+        let total = self.candidates.len();
+        let asked: Vec<&Question> = self.answers.iter().map(|(question, _)| question).collect();
+        let mut best: Option<(f64, Question)> = None;
+        for question in self.questions() {
+            if asked.contains(&&question) {
+                continue
+            }
+            let yes = self.candidates().filter(|animal| question.applies(animal)).count();
+            let gain = split_entropy(yes, total);
+            if gain > best.as_ref().map_or(0.0, |(best_gain, _)| *best_gain) {
+                best = Some((gain, question));
+            }
+        }
+        best.map(|(_, question)| question)
+    }
+
+    pub fn next_step(&self) -> ReverseStep {
+        if self.candidates.is_empty() || self.guesses.len() >= MAX_REVERSE_GUESSES {
+            return ReverseStep::GiveUp
+        }
+        if self.candidates.len() > 1 && self.answers.len() < MAX_QUESTIONS {
+            if let Some(question) = self.best_question() {
+                return ReverseStep::Ask(question)
+            }
+        }
+        ReverseStep::Guess(self.candidates[0])
+    }
+
+    /// Answer a question: the animals that don't fit are ruled out.
+    pub fn answer(&mut self, question: Question, answer: Answer) {
+        let animals = &self.animals;
+        match answer {
+            Answer::Yes => self.candidates.retain(|index| question.applies(&animals[*index])),
+            Answer::No => self.candidates.retain(|index| !question.applies(&animals[*index])),
+            Answer::DontKnow => {}
+        }
+        self.answers.push((question, answer));
+    }
+
+    /// The guess was wrong.
+    pub fn reject_guess(&mut self, index: usize) {
+        self.candidates.retain(|candidate| *candidate != index);
+        self.guesses.push(index);
+    }
+
+    /// The animal of the pack called `name`, if there is one.
+    pub fn find(&self, name: &str) -> Option<&Animal> {
+        let name = normalize_name(name);
+        self.animals.iter().find(|animal| animal.names().any(|known| normalize_name(known) == name))
+    }
+
+    /// Questions the player answered otherwise than the pack would for
+    /// `animal`, to show why it wasn't found.
+    pub fn mismatches(&self, animal: &Animal) -> Vec<&(Question, Answer)> {
+        self.answers.iter()
+            .filter(|(question, answer)| match answer {
+                Answer::Yes => !question.applies(animal),
+                Answer::No => question.applies(animal),
+                Answer::DontKnow => false,
+            })
+            .collect()
+    }
+
+    /// A new animal built from what the player told and from the yes
+    /// answers: a class or a fact they agreed to is kept.
+    pub fn learn(&self, name: &str, class: &str, features: &str) -> Animal {
+        let mut animal = Animal::new(name.trim(), &normalize_class(class), features.trim());
+        for (question, answer) in &self.answers {
+            match (question, answer) {
+                (Question::Class(class), Answer::Yes) if animal.r#type.is_empty() => animal.r#type = class.clone(),
+                (Question::Fact(tier, value), Answer::Yes) => {
+                    let field = match tier {
+                        HintTier::Habitat => &mut animal.habitat,
+                        HintTier::Continent => &mut animal.continent,
+                        HintTier::Diet => &mut animal.diet,
+                        _ => &mut animal.conservation_status,
+                    };
+                    field.get_or_insert_with(|| value.clone());
+                }
+                _ => {}
+            }
+        }
+        animal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_animals() -> Vec<Animal> {
+        vec![
+            Animal::new("Adelie Penguin", "Aves", "White eye ring"),
+            Animal::new("Emperor Penguin", "Aves", "Yellow ear patches"),
+            Animal::new("Aardvark", "Mammalia", "Long, sticky tongue"),
+            Animal::new("African Bush Elephant", "Mammalia", "Large, rounded ears"),
+            Animal::new("Green Sea Turtle", "Reptilia", "Heart-shaped shell"),
+        ]
+    }
+
+    #[test]
+    fn questions_split_the_animals_evenly() {
+        assert_eq!(split_entropy(2, 4), 1.0);
+        assert_eq!(split_entropy(0, 4), 0.0);
+        let mut game = ReverseGame::new(test_animals());
+        // Two of five share a class and a name word; class comes first.
+        assert_eq!(game.next_step(), ReverseStep::Ask(Question::Class("Aves".to_string())));
+        game.answer(Question::Class("Aves".to_string()), Answer::Yes);
+        assert_eq!(game.candidates().count(), 2);
+        let question = match game.next_step() {
+            ReverseStep::Ask(question) => question,
+            step => panic!("expected a question, got {:?}", step),
+        };
+        game.answer(question, Answer::No);
+        assert!(matches!(game.next_step(), ReverseStep::Guess(_)));
+    }
+
+    #[test]
+    fn wrong_answers_end_in_learning() {
+        let mut game = ReverseGame::new(test_animals());
+        game.answer(Question::Class("Reptilia".to_string()), Answer::Yes);
+        game.answer(Question::FeatureWord("shell".to_string()), Answer::No);
+        assert_eq!(game.next_step(), ReverseStep::GiveUp);
+        let turtle = game.find("green sea-turtle").unwrap();
+        assert_eq!(game.mismatches(turtle).len(), 1);
+        let learned = game.learn("Leopard Tortoise", "", "Spotted dome shell");
+        assert_eq!(learned.r#type, "Reptilia");
+    }
+
+    #[test]
+    fn answers_in_the_session_language() {
+        let french = Catalog::builtin("fr").unwrap();
+        assert_eq!(Answer::parse("Oui", &french), Some(Answer::Yes));
+        assert_eq!(Answer::parse("n", &french), Some(Answer::No));
+        assert_eq!(Answer::parse("?", &french), Some(Answer::DontKnow));
+        assert_eq!(Answer::parse("peut-être", &french), None);
+    }
+}
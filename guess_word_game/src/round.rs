@@ -0,0 +1,176 @@
+use crate::game::GuessRecord;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use crate::matching::{LetterFeedback, fold_letter};
+
+/// The secret animal of one round and what the players found out about
+/// it so far. Shared by solo sessions and multiplayer games.
+#[derive(Debug, Clone, Default)]
+pub struct Round {
+    secret_index: usize,
+    answer: Vec<char>,
+    guess_hint: Vec<char>,
+    history: Vec<GuessRecord>,
+    /// Letters guessed one at a time, folded like fold_letter.
+    letters: Vec<char>,
+}
+
+impl Round {
+    /// Hide every letter of `answer`, or all but the first letter of each
+    /// word when `reveal_first_letters` is set.
+    ///
+    /// Combining marks, like the vowel signs of Devanagari, are hidden and
+    /// revealed with the letter they belong to.
+    pub fn new(secret_index: usize, answer: &str, reveal_first_letters: bool) -> Round {
+        // Composed, so the letters line up with a normalised guess.
+        let answer: Vec<char> = answer.nfc().collect();
+        let guess_hint = answer.iter()
+            .map(|char_| if char_.is_whitespace() { ' ' } else { '-' })
+            .collect();
+        let mut round = Round {
+            secret_index,
+            answer,
+            guess_hint,
+            history: Vec::new(),
+            letters: Vec::new(),
+        };
+        if reveal_first_letters {
+            round.reveal_first_letters();
+        }
+        round
+    }
+
+    pub fn secret_index(&self) -> usize {
+        self.secret_index
+    }
+
+    fn is_mark(&self, index: usize) -> bool {
+        is_combining_mark(self.answer[index])
+    }
+
+    pub fn hint(&self) -> String {
+        (0..self.guess_hint.len())
+            .filter(|&index| !(self.is_mark(index) && self.guess_hint[index] == '-'))
+            .map(|index| self.guess_hint[index])
+            .collect()
+    }
+
+    /// Number of letters in the secret name, spaces excluded.
+    pub fn word_len(&self) -> usize {
+        (0..self.answer.len()).filter(|&index| !self.answer[index].is_whitespace() && !self.is_mark(index)).count()
+    }
+
+    /// Positions of the letters still hidden in the hint.
+    pub fn hidden_letters(&self) -> Vec<usize> {
+        (0..self.guess_hint.len())
+            .filter(|&index| self.guess_hint[index] == '-' && self.answer[index] != '-' && !self.is_mark(index))
+            .collect()
+    }
+
+    /// Positions of the first letter of each word that are still hidden.
+    pub fn hidden_first_letters(&self) -> Vec<usize> {
+        self.hidden_letters().into_iter()
+            .filter(|&index| index == 0 || self.answer[index - 1].is_whitespace())
+            .collect()
+    }
+
+    /// Reveal the letter at `index` and its combining marks.
+    pub fn reveal_letter(&mut self, index: usize) -> char {
+        self.guess_hint[index] = self.answer[index];
+        let mut mark = index + 1;
+        while mark < self.answer.len() && self.is_mark(mark) {
+            self.guess_hint[mark] = self.answer[mark];
+            mark += 1;
+        }
+        self.answer[index]
+    }
+
+    /// Reveal the first letter of each word. Returns how many were hidden.
+    pub fn reveal_first_letters(&mut self) -> usize {
+        let hidden = self.hidden_first_letters();
+        for &index in &hidden {
+            self.reveal_letter(index);
+        }
+        hidden.len()
+    }
+
+    /// Wrong guesses of the round, oldest first.
+    pub fn history(&self) -> &[GuessRecord] {
+        &self.history
+    }
+
+    /// Letters guessed one at a time, in the order they were tried.
+    pub fn letters(&self) -> &[char] {
+        &self.letters
+    }
+
+    /// Hangman: reveal every hidden occurrence of `letter`, ignoring case
+    /// and accents. Returns how many were revealed, or None if the letter
+    /// was already tried.
+    pub fn guess_letter(&mut self, letter: char) -> Option<usize> {
+        let letter = fold_letter(letter);
+        if self.letters.contains(&letter) {
+            return None
+        }
+        self.letters.push(letter);
+        let found: Vec<usize> = self.hidden_letters().into_iter()
+            .filter(|&index| fold_letter(self.answer[index]) == letter)
+            .collect();
+        for &index in &found {
+            self.reveal_letter(index);
+        }
+        Some(found.len())
+    }
+
+    /// Keep a wrong guess in the history without revealing anything.
+    pub fn remember_guess(&mut self, guess: &str, feedback: &[LetterFeedback], message: &str) {
+        self.history.push(GuessRecord { guess: guess.to_string(), feedback: feedback.to_vec(), message: message.to_string() });
+    }
+
+    /// Reveal the letters a wrong guess got right and keep it in the
+    /// history. Returns how many letters matched.
+    pub fn record_wrong_guess(&mut self, guess: &str, feedback: &[LetterFeedback], message: &str) -> usize {
+        let mut matched = 0;
+        for (char_index, letter) in feedback.iter().enumerate() {
+            if let LetterFeedback::Correct(match_char) = letter {
+                if !match_char.is_whitespace() && char_index < self.guess_hint.len() && !self.is_mark(char_index) {
+                    self.reveal_letter(char_index);
+                    matched += 1;
+                }
+            }
+        }
+        self.remember_guess(guess, feedback, message);
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{letter_feedback, normalize_name};
+
+    #[test]
+    fn accented_and_non_latin_names_are_masked_by_letter() {
+        let mut round = Round::new(0, "Oryctérope", false);
+        let guess = normalize_name("Orycterape");
+        let matched = round.record_wrong_guess("Orycterape", &letter_feedback(&guess, &normalize_name("Oryctérope")), "");
+        assert_eq!(matched, 9);
+        assert_eq!(round.hint(), "Oryctér-pe");
+        // "बाघ" is two letters: ब with the vowel sign ा, and घ.
+        let mut round = Round::new(0, "बाघ", false);
+        assert_eq!((round.hint(), round.word_len()), ("--".to_string(), 2));
+        round.reveal_letter(0);
+        assert_eq!(round.hint(), "बा-");
+        assert_eq!(round.hidden_letters(), vec![2]);
+    }
+
+    #[test]
+    fn letters_reveal_every_occurrence() {
+        let mut round = Round::new(0, "Oryctérope", false);
+        assert_eq!(round.guess_letter('E'), Some(2));
+        assert_eq!(round.hint(), "-----é---e");
+        assert_eq!(round.guess_letter('é'), None);
+        assert_eq!(round.guess_letter('z'), Some(0));
+        assert_eq!(round.letters(), &['e', 'z']);
+    }
+}
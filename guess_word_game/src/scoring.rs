@@ -0,0 +1,85 @@
+use std::time::Duration;
+use crate::mode::GameMode;
+
+/// Seconds of a round timer worth one point when the animal is found.
+pub const SECONDS_PER_TIME_POINT: u64 = 5;
+/// Points for finding the animal with the first guess.
+pub const FIRST_TRY_BONUS: i64 = 3;
+/// Percent added to the round points for each win in a row after the first.
+pub const STREAK_STEP_PERCENT: i64 = 25;
+pub const MAX_STREAK_PERCENT: i64 = 100;
+/// Percent taken off the round points for each hint of the round, on top of
+/// what the hint cost.
+pub const HINT_PENALTY_PERCENT: i64 = 10;
+pub const MAX_HINT_PENALTY_PERCENT: i64 = 50;
+/// Hangman: points for each letter still hidden when the whole name is
+/// found.
+pub const WORD_BONUS_PER_LETTER: i64 = 1;
+/// Points lost when a round is skipped or lost.
+pub const LOST_ROUND_PENALTY: i64 = 1;
+
+/// Score bonus for the time left on the round timer.
+pub fn time_bonus(remaining: Duration) -> i64 {
+    (remaining.as_secs() / SECONDS_PER_TIME_POINT) as i64
+}
+
+/// How the points of a won round add up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RoundScore {
+    pub guesses_left: i64,
+    pub time_bonus: i64,
+    pub first_try_bonus: i64,
+    /// Hangman: bonus for guessing the whole name.
+    pub word_bonus: i64,
+    /// Wins in a row, this one included.
+    pub streak: u64,
+    /// Percent added for the streak.
+    pub streak_percent: i64,
+    /// Percent taken off for the hints of the round.
+    pub hint_percent: i64,
+    pub multiplier: i64,
+}
+
+impl RoundScore {
+    /// Score a round won with `guess_count` of `max_guesses` wrong guesses,
+    /// as the `streak`th win in a row, after taking `hints` hints.
+    pub fn new(mode: GameMode, guess_count: i64, max_guesses: i64, time_left: Option<Duration>, streak: u64, hints: usize) -> RoundScore {
+        RoundScore {
+            guesses_left: max_guesses - guess_count,
+            time_bonus: time_left.map_or(0, time_bonus),
+            first_try_bonus: if guess_count == 0 && hints == 0 { FIRST_TRY_BONUS } else { 0 },
+            word_bonus: 0,
+            streak,
+            streak_percent: (streak.saturating_sub(1) as i64 * STREAK_STEP_PERCENT).min(MAX_STREAK_PERCENT),
+            hint_percent: (hints as i64 * HINT_PENALTY_PERCENT).min(MAX_HINT_PENALTY_PERCENT),
+            multiplier: mode.score_multiplier(),
+        }
+    }
+
+    /// Points before the percentages and the mode multiplier.
+    pub fn base(&self) -> i64 {
+        self.guesses_left + self.time_bonus + self.first_try_bonus + self.word_bonus
+    }
+
+    pub fn total(&self) -> i64 {
+        self.base() * (100 + self.streak_percent - self.hint_percent) / 100 * self.multiplier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaks_raise_and_hints_lower_the_points() {
+        let plain = RoundScore::new(GameMode::Medium, 4, 12, None, 1, 0);
+        assert_eq!(plain.total(), 8 * 2);
+        let first_try = RoundScore::new(GameMode::Easy, 0, 12, Some(Duration::from_secs(12)), 1, 0);
+        assert_eq!(first_try.total(), 12 + 2 + FIRST_TRY_BONUS);
+        assert_eq!(RoundScore::new(GameMode::Medium, 4, 12, None, 3, 0).total(), 12 * 2);
+        assert_eq!(RoundScore::new(GameMode::Medium, 4, 12, None, 20, 0).streak_percent, MAX_STREAK_PERCENT);
+        let hinted = RoundScore::new(GameMode::Hard, 0, 6, None, 1, 2);
+        assert_eq!(hinted.first_try_bonus, 0);
+        assert_eq!(hinted.total(), 6 * 80 / 100 * 3);
+    }
+}
@@ -0,0 +1,266 @@
+// Full-screen terminal front end drawn on the alternate screen.
+use crossterm::{AlternateScreen, AsyncReader, Attribute, ClearType, Color, Colored, Crossterm, InputEvent, KeyEvent};
+use std::io::{self, Write};
+use std::sync::Arc;
+use guess_word_game::{Catalog, GameView, LetterFeedback, Picture, ScreenLayout, MESSAGE_ROWS, capitalize, fit_text, gallows, visible_history};
+
+/// One line of the screen as drawn: its text and what is drawn over its
+/// right side from a column on.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ScreenRow {
+    text: String,
+    overlay: Option<(u16, String)>,
+}
+
+pub struct Tui {
+    cterm: Crossterm,
+    // Leaves the alternate screen when dropped.
+    _screen: AlternateScreen,
+    input: AsyncReader,
+    layout: ScreenLayout,
+    messages: Vec<(Color, String)>,
+    thinking: Option<usize>,
+    // Drawn over the right side of the history panel.
+    picture: Option<Picture>,
+    line: String,
+    // Header drawn last, it changes every second on timed rounds.
+    drawn_header: String,
+    // What is on screen, row by row, so a redraw only rewrites what changed.
+    drawn_rows: Vec<ScreenRow>,
+    dirty: bool,
+    catalog: Arc<Catalog>,
+}
+
+pub fn feedback_color(letter: &LetterFeedback) -> Color {
+    match letter {
+        LetterFeedback::Correct(_) => Color::Green,
+        LetterFeedback::Present(_) => Color::Yellow,
+        LetterFeedback::Absent(_) => Color::DarkGrey,
+    }
+}
+
+impl Tui {
+    /// Game screen with the panel titles of `catalog`.
+    pub fn new(catalog: Arc<Catalog>) -> io::Result<Tui> {
+        let screen = AlternateScreen::to_alternate(true)?;
+        let cterm = Crossterm::new();
+        let input = cterm.input().read_async();
+        let (width, height) = cterm.terminal().terminal_size();
+        let layout = ScreenLayout::new(width, height);
+        cterm.terminal().clear(ClearType::All)?;
+        Ok(Tui {
+            cterm,
+            _screen: screen,
+            input,
+            layout,
+            messages: Vec::new(),
+            thinking: None,
+            picture: None,
+            line: String::new(),
+            drawn_header: String::new(),
+            drawn_rows: vec![ScreenRow::default(); layout.height as usize],
+            dirty: true,
+            catalog,
+        })
+    }
+
+    pub fn catalog(&self) -> Arc<Catalog> {
+        self.catalog.clone()
+    }
+
+    pub fn push_message(&mut self, color: Color, message: &str) {
+        self.messages.push((color, message.to_string()));
+        self.dirty = true;
+    }
+
+    /// Number of "thinking" dots to show, or None to hide them.
+    pub fn set_thinking(&mut self, dots: Option<usize>) {
+        if self.thinking != dots {
+            self.thinking = dots;
+            self.dirty = true;
+        }
+    }
+
+    /// Cells a picture may use: a third of the width, the history height.
+    pub fn picture_area(&self) -> (u16, u16) {
+        (self.layout.width / 3, self.layout.history_rows)
+    }
+
+    pub fn show_picture(&mut self, picture: Picture) {
+        self.picture = Some(picture);
+        self.dirty = true;
+    }
+
+    pub fn clear_picture(&mut self) {
+        if self.picture.take().is_some() {
+            self.dirty = true;
+        }
+    }
+
+    fn draw_picture(&self, screen: &mut [ScreenRow]) {
+        let picture = match &self.picture {
+            Some(picture) => picture,
+            None => return,
+        };
+        let (columns, rows) = self.picture_area();
+        // Shown as loaded: a picture that no longer fits after a resize is skipped.
+        if picture.width() > columns || picture.height() > rows {
+            return
+        }
+        let column = self.layout.width - picture.width() - 1;
+        for (row, cells) in picture.cells.iter().enumerate() {
+            let mut line = String::new();
+            for (top, bottom) in cells {
+                line.push_str(&format!("{}{}{}",
+                    Colored::Fg(Color::Rgb { r: top[0], g: top[1], b: top[2] }),
+                    Colored::Bg(Color::Rgb { r: bottom[0], g: bottom[1], b: bottom[2] }),
+                    Picture::HALF_BLOCK));
+            }
+            line.push_str(&Attribute::Reset.to_string());
+            screen[(self.layout.history_row + 1) as usize + row].overlay = Some((column, line));
+        }
+    }
+
+    // Hangman: the gallows where a picture would go, unless one is shown.
+    fn draw_gallows(&self, screen: &mut [ScreenRow], misses: i64, lives: i64) {
+        if self.picture.is_some() {
+            return
+        }
+        let lines = gallows(misses, lives);
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16;
+        if lines.len() as u16 > self.layout.history_rows || width + 1 > self.layout.width {
+            return
+        }
+        for (row, line) in lines.iter().enumerate() {
+            let overlay = format!("{}{}{}{}", Colored::Fg(Color::Red), Attribute::Bold, line, Attribute::Reset);
+            screen[(self.layout.history_row + 1) as usize + row].overlay = Some((self.layout.width - width - 1, overlay));
+        }
+    }
+
+    /// Overwrite a line with `text` and blank the rest of it.
+    fn write_at(&self, row: u16, text: &str) {
+        self.cterm.cursor().goto(0, row).unwrap();
+        print!("{}{}{}", Colored::Fg(Color::White), text, Attribute::Reset);
+        self.cterm.terminal().clear(ClearType::UntilNewLine).unwrap();
+    }
+
+    /// Redraw every panel from the game state. Only the lines that differ
+    /// from the screen are written; the whole screen is cleared only when
+    /// the terminal was resized.
+    pub fn draw(&mut self, session: &dyn GameView, player: &str) {
+        let (width, height) = self.cterm.terminal().terminal_size();
+        let layout = ScreenLayout::new(width, height);
+        if layout != self.layout {
+            self.layout = layout;
+            self.cterm.terminal().clear(ClearType::All).unwrap();
+            self.drawn_rows = vec![ScreenRow::default(); layout.height as usize];
+        }
+        let mut rows = vec![ScreenRow::default(); layout.height as usize];
+        let mut set = |row: u16, text: String| rows[row as usize].text = text;
+
+        self.drawn_header = session.header(player);
+        self.dirty = false;
+        let header = fit_text(&self.drawn_header, layout.width);
+        set(layout.header_row, format!("{}{}{}", Colored::Fg(Color::Yellow), Attribute::Bold, header));
+        for row in layout.separator_rows().iter() {
+            set(*row, "-".repeat(layout.width as usize));
+        }
+
+        let catalog = self.catalog.clone();
+        let mut hint = format!(" {}: {} [{}]", catalog.text("panel-hint"), session.hint(), session.word_len());
+        if let Some(puzzle) = session.puzzle() {
+            hint = format!(" {}: {} |{}", catalog.text("panel-puzzle"), puzzle, hint);
+        }
+        if !session.letters().is_empty() {
+            let letters: String = session.letters().iter().flat_map(|letter| letter.to_uppercase()).collect();
+            hint.push_str(&format!(" | {}: {}", catalog.text("panel-letters"), letters));
+        }
+        let mut family = format!(" {}: {}", catalog.text("panel-family"), session.visible_type().unwrap_or("???"));
+        for (tier, fact) in session.visible_facts() {
+            family.push_str(&format!(" | {}: {}", capitalize(catalog.text(tier.message_key())), fact));
+        }
+        let features = format!(" {}: {}", catalog.text("panel-features"), session.visible_features().unwrap_or("???"));
+        set(layout.hint_row, format!("{}{}{}", Colored::Fg(Color::Green), Attribute::Bold, fit_text(&hint, layout.width)));
+        set(layout.hint_row + 1, fit_text(&family, layout.width));
+        set(layout.hint_row + 2, fit_text(&features, layout.width));
+
+        set(layout.history_row, fit_text(&format!(" {}:", catalog.text("panel-history")), layout.width));
+        for (row, record) in visible_history(session.history(), layout.history_rows).iter().enumerate() {
+            let mut line = String::from(" ");
+            for letter in &record.feedback {
+                line.push_str(&format!("{}{}", Colored::Fg(feedback_color(letter)), letter.letter().to_uppercase()));
+            }
+            let used = record.feedback.len() as u16 + 1;
+            let message = fit_text(&format!("  {}", record.message), layout.width.saturating_sub(used));
+            set(layout.history_row + 1 + row as u16, format!("{}{}{}{}", Attribute::Bold, line, Colored::Fg(Color::Blue), message));
+        }
+
+        let mut messages = self.messages.clone();
+        if let Some(dots) = self.thinking {
+            messages.push((Color::White, ".".repeat(dots)));
+        }
+        let first_message = messages.len().saturating_sub(MESSAGE_ROWS as usize);
+        for (row, (color, message)) in messages[first_message..].iter().enumerate() {
+            set(layout.message_row + row as u16, format!("{}{}", Colored::Fg(*color), fit_text(message, layout.width)));
+        }
+        self.draw_picture(&mut rows);
+        if let Some((misses, lives)) = session.misses() {
+            self.draw_gallows(&mut rows, misses, lives);
+        }
+
+        for (row, (line, drawn)) in rows.iter().zip(&self.drawn_rows).enumerate() {
+            if line == drawn {
+                continue
+            }
+            self.write_at(row as u16, &line.text);
+            if let Some((column, overlay)) = &line.overlay {
+                self.cterm.cursor().goto(*column, row as u16).unwrap();
+                print!("{}", overlay);
+            }
+        }
+        self.drawn_rows = rows;
+        self.draw_input();
+    }
+
+    fn draw_input(&self) {
+        let prompt = format!("> {}", self.line);
+        self.write_at(self.layout.input_row, &fit_text(&prompt, self.layout.width.saturating_sub(1)));
+        let column = (prompt.chars().count() as u16).min(self.layout.width - 1);
+        self.cterm.cursor().goto(column, self.layout.input_row).unwrap();
+        io::stdout().flush().unwrap();
+    }
+
+    /// Redraw if anything on screen changed: new messages, the game
+    /// header (score, timers) or the terminal size.
+    pub fn refresh(&mut self, session: &dyn GameView, player: &str) {
+        let (width, height) = self.cterm.terminal().terminal_size();
+        if self.dirty || ScreenLayout::new(width, height) != self.layout || session.header(player) != self.drawn_header {
+            self.draw(session, player);
+        }
+    }
+
+    /// Handle the keys typed since the last call. Returns the line once Enter is pressed.
+    pub fn poll_line(&mut self) -> Option<String> {
+        while let Some(event) = self.input.next() {
+            match event {
+                InputEvent::Keyboard(KeyEvent::Char('\n')) | InputEvent::Keyboard(KeyEvent::Char('\r')) => {
+                    let line = self.line.trim().to_string();
+                    self.line.clear();
+                    self.draw_input();
+                    return Some(line)
+                }
+                InputEvent::Keyboard(KeyEvent::Ctrl('c')) => return Some("quit".to_string()),
+                InputEvent::Keyboard(KeyEvent::Backspace) => {
+                    self.line.pop();
+                    self.draw_input();
+                }
+                InputEvent::Keyboard(KeyEvent::Char(c)) if !c.is_control() => {
+                    self.line.push(c);
+                    self.draw_input();
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,276 @@
+use crate::game::{GameSession, GuessRecord};
+use crate::hint::HintTier;
+use crate::locale::Catalog;
+use crate::mode::GameKind;
+use crate::multiplayer::PlayerScore;
+use crate::scoring::RoundScore;
+
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 16;
+/// Lines kept in the message panel under the history.
+pub const MESSAGE_ROWS: u16 = 3;
+
+/// Rows of each panel of the full-screen UI for a terminal size.
+///
+/// ```text
+/// 0      header: player, mode, score, guesses
+/// 2..=4  hint panel: masked name, family, features
+/// 6      history title, previous guesses below it
+///        message panel
+/// last   input line
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenLayout {
+    pub width: u16,
+    pub height: u16,
+    pub header_row: u16,
+    pub hint_row: u16,
+    pub history_row: u16,
+    pub history_rows: u16,
+    pub message_row: u16,
+    pub input_row: u16,
+}
+
+impl ScreenLayout {
+    pub fn new(width: u16, height: u16) -> ScreenLayout {
+        let height = height.max(MIN_HEIGHT);
+        let input_row = height - 1;
+        let message_row = input_row - 1 - MESSAGE_ROWS;
+        let history_row = 6;
+        ScreenLayout {
+            width: width.max(MIN_WIDTH),
+            height,
+            header_row: 0,
+            hint_row: 2,
+            history_row,
+            history_rows: message_row - 1 - (history_row + 1),
+            message_row,
+            input_row,
+        }
+    }
+
+    /// Rows drawn as horizontal separators between panels.
+    pub fn separator_rows(&self) -> [u16; 4] {
+        [self.header_row + 1, self.history_row - 1, self.message_row - 1, self.input_row - 1]
+    }
+}
+
+/// Cut or pad `text` to exactly `width` characters so a redraw
+/// overwrites whatever was on the line before.
+pub fn fit_text(text: &str, width: u16) -> String {
+    let width = width as usize;
+    let mut fitted: String = text.chars().take(width).collect();
+    let len = fitted.chars().count();
+    if len == width && text.chars().count() > width && width > 3 {
+        fitted = fitted.chars().take(width - 3).collect();
+        fitted.push_str("...");
+    }
+    let len = fitted.chars().count();
+    fitted.extend(std::iter::repeat_n(' ', width - len));
+    fitted
+}
+
+/// `text` with its first letter in upper case.
+pub fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn header_text(session: &GameSession, player: &str) -> String {
+    let catalog = &session.config().catalog;
+    let mode = session.config().mode;
+    let mut header = catalog.format("header", &[
+        ("player", &player),
+        ("mode", &mode.local_name(catalog)),
+        ("score", &session.score()),
+        ("guesses", &session.guess_count()),
+        ("max", &session.max_guesses()),
+    ]);
+    if session.config().kind == GameKind::Hangman {
+        header.push_str(&catalog.format("header-lives", &[("lives", &life_bar(session.guess_count(), session.max_guesses()))]));
+    }
+    if let Some(time_left) = session.round_time_left() {
+        header.push_str(&catalog.format("header-time", &[("seconds", &time_left.as_secs())]));
+    }
+    if let Some(time_left) = session.blitz_time_left() {
+        header.push_str(&catalog.format("header-blitz", &[("seconds", &time_left.as_secs())]));
+    }
+    header
+}
+
+/// Points of a won round with the bonuses and penalties that made them,
+/// e.g. "+18 points (first try +3, 2 in a row +25%)."
+pub fn round_score_text(score: &RoundScore, catalog: &Catalog) -> String {
+    let mut details = Vec::new();
+    if score.first_try_bonus > 0 {
+        details.push(catalog.format("score-first-try", &[("points", &score.first_try_bonus)]));
+    }
+    if score.time_bonus > 0 {
+        details.push(catalog.format("score-time", &[("points", &score.time_bonus)]));
+    }
+    if score.word_bonus > 0 {
+        details.push(catalog.format("score-word", &[("points", &score.word_bonus)]));
+    }
+    if score.streak_percent > 0 {
+        details.push(catalog.format("score-streak", &[("streak", &score.streak), ("percent", &score.streak_percent)]));
+    }
+    if score.hint_percent > 0 {
+        details.push(catalog.format("score-hints", &[("percent", &score.hint_percent)]));
+    }
+    if details.is_empty() {
+        return catalog.format("round-points", &[("points", &score.total())])
+    }
+    catalog.format("round-points-details", &[("points", &score.total()), ("details", &details.join(", "))])
+}
+
+/// Hangman lives as a bar, e.g. "[####--]" after 2 misses of 6.
+pub fn life_bar(misses: i64, lives: i64) -> String {
+    let misses = misses.clamp(0, lives.max(0)) as usize;
+    format!("[{}{}]", "#".repeat(lives.max(0) as usize - misses), "-".repeat(misses))
+}
+
+/// Parts of the hanged man: head, body, arms and legs.
+pub const GALLOWS_PARTS: i64 = 6;
+
+/// ASCII gallows with as many parts drawn as the misses used up of the
+/// lives, the whole man on the last miss.
+pub fn gallows(misses: i64, lives: i64) -> Vec<String> {
+    let parts = if lives <= 0 { GALLOWS_PARTS } else { (misses.max(0) * GALLOWS_PARTS + lives - 1) / lives };
+    let part = |index: i64, drawn: char| if parts >= index { drawn } else { ' ' };
+    vec![
+        "  +---+".to_string(),
+        "  |   |".to_string(),
+        format!("  {}   |", part(1, 'O')),
+        format!(" {}{}{}  |", part(3, '/'), part(2, '|'), part(4, '\\')),
+        format!(" {} {}  |", part(5, '/'), part(6, '\\')),
+        "=======".to_string(),
+    ]
+}
+
+/// Scores of every player on one line, e.g. "Ann 4, Bob 2".
+pub fn standings_text(scores: &[PlayerScore]) -> String {
+    let standings: Vec<String> = scores.iter().map(|score| format!("{} {}", score.name, score.score)).collect();
+    standings.join(", ")
+}
+
+/// What the full-screen UI draws, for solo and multiplayer games alike.
+pub trait GameView {
+    /// Header line, seen by `player`.
+    fn header(&self, player: &str) -> String;
+    fn hint(&self) -> String;
+    fn word_len(&self) -> usize;
+    fn visible_type(&self) -> Option<&str>;
+    fn visible_features(&self) -> Option<&str>;
+    /// Facts given as hints, as (tier, value) pairs.
+    fn visible_facts(&self) -> Vec<(HintTier, &str)> {
+        Vec::new()
+    }
+    fn history(&self) -> &[GuessRecord];
+    /// Hangman: letters tried this round.
+    fn letters(&self) -> &[char] {
+        &[]
+    }
+    /// Hangman: (misses, lives) for the gallows.
+    fn misses(&self) -> Option<(i64, i64)> {
+        None
+    }
+    /// Puzzle games: what the round asks.
+    fn puzzle(&self) -> Option<String> {
+        None
+    }
+}
+
+impl GameView for GameSession {
+    fn header(&self, player: &str) -> String {
+        header_text(self, player)
+    }
+
+    fn hint(&self) -> String {
+        GameSession::hint(self)
+    }
+
+    fn word_len(&self) -> usize {
+        GameSession::word_len(self)
+    }
+
+    fn visible_type(&self) -> Option<&str> {
+        GameSession::visible_type(self)
+    }
+
+    fn visible_features(&self) -> Option<&str> {
+        GameSession::visible_features(self)
+    }
+
+    fn visible_facts(&self) -> Vec<(HintTier, &str)> {
+        GameSession::visible_facts(self)
+    }
+
+    fn history(&self) -> &[GuessRecord] {
+        GameSession::history(self)
+    }
+
+    fn letters(&self) -> &[char] {
+        GameSession::letters(self)
+    }
+
+    fn misses(&self) -> Option<(i64, i64)> {
+        match self.config().kind {
+            GameKind::Hangman => Some((self.guess_count(), self.max_guesses())),
+            _ => None,
+        }
+    }
+
+    fn puzzle(&self) -> Option<String> {
+        GameSession::puzzle(self)
+    }
+}
+
+/// The most recent guesses that fit in `rows` lines.
+pub fn visible_history(history: &[GuessRecord], rows: u16) -> &[GuessRecord] {
+    let rows = rows as usize;
+    &history[history.len().saturating_sub(rows)..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_grows_history_with_height() {
+        let layout = ScreenLayout::new(80, 24);
+        assert_eq!(layout.input_row, 23);
+        assert_eq!(layout.message_row, 19);
+        assert_eq!(layout.history_rows, 11);
+        assert_eq!(ScreenLayout::new(10, 5), ScreenLayout::new(MIN_WIDTH, MIN_HEIGHT));
+        assert_eq!(ScreenLayout::new(MIN_WIDTH, MIN_HEIGHT).history_rows, 3);
+    }
+
+    #[test]
+    fn fit_text_pads_and_truncates() {
+        assert_eq!(fit_text("Emu", 5), "Emu  ");
+        assert_eq!(fit_text("Elephant", 6), "Ele...");
+        assert_eq!(fit_text("Elephant", 8), "Elephant");
+    }
+
+    #[test]
+    fn gallows_fill_up_with_misses() {
+        assert_eq!(life_bar(2, 6), "[####--]");
+        assert_eq!(life_bar(9, 6), "[------]");
+        assert_eq!(gallows(0, 6)[2], "      |");
+        assert_eq!(gallows(3, 6)[3], " /|   |");
+        // Fewer lives draw more of the man per miss.
+        assert_eq!(gallows(4, 4)[4], " / \\  |");
+        assert_eq!(gallows(1, 4)[3], "  |   |");
+    }
+
+    #[test]
+    fn history_shows_latest_guesses() {
+        let record = |guess: &str| GuessRecord { guess: guess.to_string(), feedback: Vec::new(), message: String::new() };
+        let history = vec![record("emu"), record("owl"), record("yak")];
+        assert_eq!(visible_history(&history, 2)[0].guess, "owl");
+        assert_eq!(visible_history(&history, 5).len(), 3);
+    }
+}